@@ -3,29 +3,172 @@
 // Under MIT license
 //
 
+use id_tree::Tree;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::fs::create_dir_all;
+use std::io::{Cursor, Read};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::generic_traits::generic_traits::String2StructureBuilder;
+use crate::sub_tree_children::sub_tree_children::SubChildren;
+use crate::{String2Tree, String2Conll, Tree2Plot, Tree2String, Conll2Plot, Conll2String, Structure2PlotBuilder, token_depths, tree_to_dot, conll_to_dot};
+use crate::output_format::PlotFormat;
+use crate::conll_analysis::in_cycle;
+use crate::warnings::Warning;
 
-const ARGS_LENGTH: usize = 4;
+const MIN_ARGS_LENGTH: usize = 4;
+const MAX_ARGS_LENGTH: usize = 5;
 const IMG_TYPE: &str = ".png";
 const DEPENDENCY: &str = "d";
 const CONSTITUENCY: &str = "c";
+const REPORT_HEADER: &str = "statistic,value,count";
+const CONLL_N_FIELDS: usize = 10;
+const COMMENT_PREFIX: &str = "#";
+
+///
+/// A method that splits a string of multiple conll sentences, separated by blank lines, into the lines of every sentence.
+/// The same splitting is applied when a conll file is read by Config, without touching the filesystem. Every block can
+/// be built by String2Conll.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use parsed_to_plot::split_conllu;
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// 
+/// let conll = "0\tHello\thello\tINTJ\t_\t_\t0\tROOT\t_\t_\n\n# text = Hi there\n0\tHi\thi\tINTJ\t_\t_\t0\tROOT\t_\t_\n1\tthere\tthere\tADV\t_\t_\t0\tadvmod\t_\t_\n";
+/// let mut sentences = split_conllu(conll);
+/// assert_eq!(sentences.len(), 2);
+/// 
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut sentences[1]).unwrap();
+/// assert_eq!(string2conll.get_sentence().get_text().unwrap(), "Hi there");
+/// ```
+/// 
+pub fn split_conllu(input: &str) -> Vec<Vec<String>> {
+    configure_structures::split_blocks(input.lines().map(|line| line.to_string()), None)
+}
+
+/// An enum of the input types of Config, as detected from the content of an input by detect_input_type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputType {
+    /// Bracketed constituencies, a tree per line (the "c" selector).
+    Constituency,
+    /// Conll sentences, a token per line with 10 tab separated fields (the "d" selector).
+    Dependency
+}
+
+///
+/// A method that detects whether the content of an input file holds constituencies or conll sentences, by a heuristic
+/// over its lines: lines that start with "(" suggest constituencies, lines of 10 tab separated fields suggest conll.
+/// Blank lines and comment lines (that start with "#") are ignored. Returns an error if the lines suggest both types,
+/// or neither, in which case the selector should be given explicitly.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use parsed_to_plot::{detect_input_type, InputType};
+/// 
+/// let conll = "# text = Hello\n0\tHello\thello\tINTJ\t_\t_\t0\tROOT\t_\t_\n";
+/// assert_eq!(detect_input_type(conll).unwrap(), InputType::Dependency);
+/// assert_eq!(detect_input_type("(S (NP (N Hello)))\n(S (a))").unwrap(), InputType::Constituency);
+/// assert!(detect_input_type("Hello world").is_err());
+/// ```
+/// 
+pub fn detect_input_type(input: &str) -> Result<InputType, Box<dyn Error>> {
+
+    let (mut n_constituency, mut n_dependency) = (0, 0);
+    for line in input.trim_start_matches('\u{feff}').lines().map(|line| line.trim_end_matches('\r')) {
+        if line.trim().is_empty() || line.starts_with(COMMENT_PREFIX) {
+            continue;
+        }
+        if line.trim_start().starts_with('(') {
+            n_constituency += 1;
+        } else if line.split('\t').count() == CONLL_N_FIELDS {
+            n_dependency += 1;
+        }
+    }
+
+    match (n_constituency, n_dependency) {
+        (0, 0) => Err("could not detect the input type, no line looks like a constituency or a conll token".into()),
+        (_, 0) => Ok(InputType::Constituency),
+        (0, _) => Ok(InputType::Dependency),
+        _ => Err(format!("could not detect the input type, {} lines look like constituencies and {} like conll tokens", n_constituency, n_dependency).into())
+    }
+}
+
+///
+/// A method that plots every input of a file to png images in the output dir (created if missing), with the type of
+/// the inputs detected by detect_input_type instead of the "c" or "d" selector of Config::new. The images are named
+/// by the index of the input, as in Config::build_all. Returns the detected type.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use parsed_to_plot::{auto_plot, InputType};
+/// 
+/// assert_eq!(auto_plot("Input/conll.txt", "Output/Auto").unwrap(), InputType::Dependency);
+/// assert!(std::path::Path::new("Output/Auto/0.png").exists());
+/// ```
+/// 
+pub fn auto_plot(file_path: &str, out_dir: &str) -> Result<InputType, Box<dyn Error>> {
+
+    let mut input = String::new();
+    configure_structures::open_input(file_path)?.read_to_string(&mut input)?;
+    let input_type = detect_input_type(&input)?;
+
+    let sequences = Config::from_str_input(input_type, &input, out_dir)?;
+    Config::build_all(sequences, out_dir, OutputFormat::Png)?;
+    Ok(input_type)
+}
 
 /// Host all configuration process between io and the library, including interaction with files and commandline
 pub mod configure_structures {
 
     use std::error::Error;
-    use std::fs::{File, self};
-    use std::io::{self, BufRead};
+    use std::fs::File;
+    use std::io::{self, BufRead, Write};
     use std::vec;
 
+    const BOM: char = '\u{feff}';  // byte order mark, written at the start of files by some editors
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];  // the first two bytes of every gzip stream
+
+    // A helper that strips the byte order mark from the first line of a file, if exists.
+    fn strip_bom(line: String, line_number: usize) -> String {
+        match line_number {
+            0 => line.trim_start_matches(BOM).to_string(),
+            _ => line
+        }
+    }
+
+    // A helper that opens an input file for reading lines. Gzipped files are recognized by their first two bytes
+    // rather than by the extension (files are sometimes misnamed), and decoded when compiled with the flate2 feature.
+    pub(in crate::config) fn open_input(file_path: &str) -> Result<Box<dyn BufRead>, Box<dyn Error>> {
+        let mut reader = io::BufReader::new(File::open(file_path)?);
+        let is_gzip = reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+        match is_gzip {
+            false => Ok(Box::new(reader)),
+            #[cfg(feature = "flate2")]
+            true => Ok(Box::new(io::BufReader::new(flate2::bufread::MultiGzDecoder::new(reader)))),
+            #[cfg(not(feature = "flate2"))]
+            true => Err(format!("{} is gzipped, compile with the flate2 feature to read it", file_path).into())
+        }
+    }
+
     /// Dependency is a vector of dependency string vectors.
+    /// The reading stops after head sentences, if given.
     #[derive(Clone)]
-    pub(in crate::config) struct Dependency {}
+    pub(in crate::config) struct Dependency {
+        pub(in crate::config) head: Option<usize>
+    }
 
     /// Constituency is a vector of constituency string.
+    /// The reading stops after head sentences, if given.
     #[derive(Clone)]
-    pub(in crate::config) struct Constituency {}
+    pub(in crate::config) struct Constituency {
+        pub(in crate::config) head: Option<usize>
+    }
 
     /// An enum that wraps the data types supported.
     #[derive(Clone, Debug)]
@@ -56,27 +199,36 @@ pub mod configure_structures {
         }
     }
 
+    /// Iterates over the sentences, one Vec per sentence for both types: the conll lines of a dependency,
+    /// or a single element Vec with the line of a constituency.
     impl IntoIterator for DataType {
         type Item = Vec<String>;
         type IntoIter = std::vec::IntoIter<Self::Item>;
 
         fn into_iter(self) -> Self::IntoIter {
             match self {
-                DataType::Constituency(x) => vec![x].into_iter(),
+                DataType::Constituency(x) => x.into_iter().map(|sentence| vec![sentence]).collect::<Vec<Vec<String>>>().into_iter(),
                 DataType::Dependency(x) => x.into_iter()
             }
         }
     }
 
-    /// A trait that defines that behavior for saving files throughout the library.
-    /// Used automatically, not called by the user.
-    pub(in crate) trait Saver {
-        fn save_output(&self, out_path: &str) -> Result<(), Box<dyn Error>>;
+    /// A trait that defines that behavior for saving outputs throughout the library.
+    /// Implementors write to any sink that implements Write (a file, an in-memory buffer, etc.),
+    /// save_output is a convenience for writing to a file path.
+    pub trait Saver {
+        fn write_output<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>>;
+
+        fn save_output(&self, out_path: &str) -> Result<(), Box<dyn Error>> {
+            let mut out_file = File::create(out_path)?;
+            self.write_output(&mut out_file)?;
+            Ok(())
+        }
     }
 
     impl Saver for Vec<Vec<String>> {
 
-        fn save_output(&self, out_path: &str) -> Result<(), Box<dyn Error>> {
+        fn write_output<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
             
             // each string is a token => line with fields sep by tab
             let mut out_vec = Vec::new();
@@ -84,79 +236,187 @@ pub mod configure_structures {
                 let string_vec = vec.join("\n").to_owned();
                 out_vec.push(string_vec);
             }
-            out_vec.save_output(out_path)?;
+            out_vec.write_output(writer)?;
             Ok(())
         }
     }
 
     impl Saver for Vec<String> {
 
-        fn save_output(&self, out_path: &str) -> Result<(), Box<dyn Error>> {
+        fn write_output<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
 
             let out_string = self.join("\n");
-            fs::write(out_path, out_string).expect("Unable to write file");            
+            writer.write_all(out_string.as_bytes())?;
             Ok(())
         }
     }
 
-    /// A trait that supplies reading functionallity over input files.
+    /// A trait that supplies reading functionallity over any BufRead (an input file, a Cursor over a string, etc.),
+    /// read_input is a convenience for reading an input file.
     /// The trait is used from within the config implementation.
     /// Not called directly by the user.
     pub (in crate::config) trait Reader {
         type Out;
-        fn read_input(&self, file_path: &str) -> Result<Self::Out, Box<dyn Error>>;
+        fn read_from<R: BufRead>(&self, reader: R, source: &str) -> Result<Self::Out, Box<dyn Error>>;
+
+        fn read_input(&self, file_path: &str) -> Result<Self::Out, Box<dyn Error>> {
+            self.read_from(open_input(file_path)?, file_path)
+        }
     }
 
     impl Reader for Dependency {
         type Out = DataType;
-        fn read_input(&self, file_path: &str) -> Result<Self::Out, Box<dyn Error>> {
+        fn read_from<R: BufRead>(&self, reader: R, _source: &str) -> Result<Self::Out, Box<dyn Error>> {
 
-            // load dependencies
-            let in_file = File::open(file_path)?; 
-            let lines = io::BufReader::new(in_file).lines();
+            // load dependencies, the lines are streamed so that the rest of the input is not read after the head.
+            // the lines stop at the first error, which is returned
+            let mut error: Option<io::Error> = None;
+            let lines = reader.lines().map_while(|line| line.map_err(|e| error = Some(e)).ok());
+            let sequences = split_blocks(lines, self.head);
+            match error {
+                Some(e) => Err(e.into()),
+                None => Ok(DataType::Dependency(sequences))
+            }
 
-            let mut sequences = Vec::new();
-            let mut depencdency: Vec<String> = Vec::new();
-            for (i, line) in lines.enumerate() {
-                
-                // skip empty first line is exists
-                if i == 0 && line.as_ref().unwrap().trim().is_empty() {
-                    continue;
-                }
+        }
+    }
 
-                if line.as_ref().unwrap().trim().is_empty() {
-                    sequences.push(depencdency);
-                    depencdency = Vec::new();
-                } else {
-                    depencdency.push(line.unwrap());
-                }
+    // A helper that splits lines of conll into the blocks of the sentences, separated by blank lines.
+    // No more lines are taken once head blocks are complete, if given.
+    pub(in crate::config) fn split_blocks<I: IntoIterator<Item = String>>(lines: I, head: Option<usize>) -> Vec<Vec<String>> {
+
+        let mut sequences = Vec::new();
+        let mut depencdency: Vec<String> = Vec::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            
+            if head.is_some_and(|head| sequences.len() >= head) {
+                break;
             }
+            let line = strip_bom(line, i);
 
-            if depencdency.len() > 0 {
-                sequences.push(depencdency);
+            // skip empty first line is exists
+            if i == 0 && line.trim().is_empty() {
+                continue;
             }
 
-            return Ok(DataType::Dependency(sequences))
+            // consecutive blank lines separate a single pair of sentences
+            if line.trim().is_empty() {
+                if !depencdency.is_empty() {
+                    sequences.push(depencdency);
+                    depencdency = Vec::new();
+                }
+            } else {
+                depencdency.push(line);
+            }
+        }
 
+        if !depencdency.is_empty() {
+            sequences.push(depencdency);
         }
+        sequences
     }
 
 
     impl Reader for Constituency {
         type Out = DataType;
-        fn read_input(&self, file_path: &str) -> Result<Self::Out, Box<dyn Error>> {
+        fn read_from<R: BufRead>(&self, reader: R, _source: &str) -> Result<Self::Out, Box<dyn Error>> {
 
-            let in_file = File::open(file_path)?; 
-            let lines = io::BufReader::new(in_file).lines();
-            let sequences = lines.map(|line| line
-                .expect("un string-like line"))
-                .collect::<Vec<String>>();
+            let lines = reader.lines();
+            // surrounding whitespace (and a byte order mark on the first line) is removed, and blank lines
+            // (like trailing empty lines) are skipped, since they do not hold a tree
+            let mut sequences = Vec::new();
+            for (i, line) in lines.enumerate() {
+                if self.head.is_some_and(|head| sequences.len() >= head) {
+                    break;
+                }
+                let line = strip_bom(line.expect("un string-like line"), i);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                sequences.push(line.trim().to_string());
+            }
             
             return Ok(DataType::Constituency(sequences))
         }
     }
 }
 
+// A helper that draws n distinct indices out of n_total uniformly, returned in ascending order.
+// A partial Fisher-Yates shuffle driven by a splitmix64 generator, so a given seed always draws the same sample.
+fn sample_indices(n_total: usize, n: usize, seed: u64) -> Vec<usize> {
+    let mut state = seed;
+    let mut next = |bound: usize| {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        ((z ^ (z >> 31)) % bound as u64) as usize
+    };
+
+    let mut indices = (0..n_total).collect::<Vec<usize>>();
+    let n = n.min(n_total);
+    for i in 0..n {
+        let j = i + next(n_total - i);
+        indices.swap(i, j);
+    }
+    let mut sample = indices[..n].to_vec();
+    sample.sort();
+    sample
+}
+
+// A helper that quotes a csv field if it holds a delimiter or a quote (labels are free text).
+fn csv_field(field: &str) -> String {
+    match field.contains(',') || field.contains('"') {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string()
+    }
+}
+
+/// An enum of the output formats that can be selected from the command line, by an optional last argument
+/// ("png", "jpeg", "gif", "svg", "pdf", "dot" or "txt", default png).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// A png image of the plot.
+    Png,
+    /// A jpeg image of the plot.
+    Jpeg,
+    /// A gif image of the plot (a constituency is animated, see Tree2Plot::set_animate).
+    Gif,
+    /// An svg image of the plot.
+    Svg,
+    /// A pdf of the plot, requires the pdf feature.
+    Pdf,
+    /// The structure in the dot language of graphviz (see tree_to_dot and conll_to_dot).
+    Dot,
+    /// The reconstructed string of the structure (a bracketed constituency or a conll).
+    Txt
+}
+
+impl OutputFormat {
+
+    // A helper method that returns the format of the plotting modules of an image format, and None for the formats
+    // that are written as text. The extension of an image is taken from it, so the plotting modules save it as such.
+    fn plot_format(&self) -> Option<PlotFormat> {
+        match self {
+            OutputFormat::Png => Some(PlotFormat::Png),
+            OutputFormat::Jpeg => Some(PlotFormat::Jpeg),
+            OutputFormat::Gif => Some(PlotFormat::Gif),
+            OutputFormat::Svg => Some(PlotFormat::Svg),
+            OutputFormat::Pdf => Some(PlotFormat::Pdf),
+            OutputFormat::Dot | OutputFormat::Txt => None
+        }
+    }
+
+    // A helper method that returns the extension of the output files, with the leading dot.
+    fn extension(&self) -> &str {
+        match (self, self.plot_format()) {
+            (_, Some(plot_format)) => plot_format.extension(),
+            (OutputFormat::Dot, None) => ".dot",
+            (_, None) => ".txt"
+        }
+    }
+}
+
 /// An empty struct of configuration process 
 #[derive(PartialEq)]
 #[derive(Debug)]
@@ -183,33 +443,425 @@ impl Config {
         }
     }
 
+    ///
+    /// A get method to retrive the complete output path in the requested output format (see get_out_format)
+    /// 
+    pub fn get_out_file_as(out_dir_path: &str, file_name: &str, format: OutputFormat) -> String {
+        out_dir_path.to_string() + "/" + file_name + format.extension()
+    }
+
     ///
     /// The Config trait receives the command line array of inputs and parses it.
     /// Expects 3 arguments : Letter selector, input text file, Requested output path to save png images.
+    /// An optional 4th argument selects the output format, see get_out_format.
     /// Returns a Result over DataType.
     /// 
     /// See lib.rs file for examples
     /// 
     pub fn new(args: &[String]) -> Result<DataType, Box<dyn Error>> {
+        Config::read(args, None)
+    }
+
+    ///
+    /// A method like Config::new that reads only the first n sentences of the input file, for quick previews of large
+    /// corpora. The reading stops after n sentences (blank lines between constituencies are not counted), so the rest
+    /// of the file is not read. A file with fewer sentences is read whole.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// 
+    /// let args = ["PROGRAM_NAME", "d", "Input/conll.txt", "Output"].map(|x| x.to_string()).to_vec();
+    /// let sequences = Config::head(&args, 1).unwrap();
+    /// assert_eq!(sequences.into_iter().count(), 1);
+    /// ```
+    /// 
+    pub fn head(args: &[String], n: usize) -> Result<DataType, Box<dyn Error>> {
+        Config::read(args, Some(n))
+    }
+
+    // A helper method that parses the command line array and reads the input file, up to head sentences if given.
+    fn read(args: &[String], head: Option<usize>) -> Result<DataType, Box<dyn Error>> {
 
         // validate number of arguments supplied
-        if args.len() != ARGS_LENGTH {
-            let custom_err = format!("there should be {} arguments supplied: constituency file and output dir, found {}", ARGS_LENGTH, args.len());
+        if !(MIN_ARGS_LENGTH..=MAX_ARGS_LENGTH).contains(&args.len()) {
+            let custom_err = format!("there should be {} or {} arguments supplied: input selector, input file, output dir and an optional \
+            output format, found {}", MIN_ARGS_LENGTH, MAX_ARGS_LENGTH, args.len());
             return Err(custom_err.into());
         }
+        Config::get_out_format(args)?;
 
         // load output directory path and try to create it
         Config::make_out_dir(&args[3])?;
 
         // load inputs
         if CONSTITUENCY == args[1] {
-            return Box::new (Constituency {}).read_input(&args[2]);
+            Constituency { head }.read_input(&args[2])
         } else if DEPENDENCY == args[1] {
-            return Box::new (Dependency {}).read_input(&args[2]);
+            Dependency { head }.read_input(&args[2])
         } else {
-            return Err(format!("Resulted in error in parsing: input selector {} is invalid", args[1]).into());
+            Err(format!("Resulted in error in parsing: input selector {} is invalid", args[1]).into())
+        }
+
+    }
+
+    ///
+    /// A method that reads the inputs from a string rather than from a file, the same way Config::new reads an input
+    /// file of the given type: a constituency per line, or conll sentences separated by blank lines. The output dir
+    /// is created as in Config::new.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::{Config, InputType};
+    /// 
+    /// let sequences = Config::from_str_input(InputType::Constituency, "(S (NP (N Hello)))\n(S (a))\n", "Output").unwrap();
+    /// assert_eq!(sequences.into_iter().count(), 2);
+    /// ```
+    /// 
+    pub fn from_str_input(input_type: InputType, content: &str, out_dir: &str) -> Result<DataType, Box<dyn Error>> {
+
+        Config::make_out_dir(&out_dir.to_string())?;
+        let reader = Cursor::new(content);
+        match input_type {
+            InputType::Constituency => Constituency { head: None }.read_from(reader, "the input string"),
+            InputType::Dependency => Dependency { head: None }.read_from(reader, "the input string")
+        }
+    }
+
+    ///
+    /// A method that parses the optional output format argument of the command line array: "png", "jpeg" (or "jpg"),
+    /// "gif", "svg", "pdf", "dot" or "txt". Without the argument the output format is png.
+    /// 
+    pub fn get_out_format(args: &[String]) -> Result<OutputFormat, Box<dyn Error>> {
+        match args.get(MAX_ARGS_LENGTH - 1).map(|x| x.as_str()) {
+            None | Some("png") => Ok(OutputFormat::Png),
+            Some("jpeg" | "jpg") => Ok(OutputFormat::Jpeg),
+            Some("gif") => Ok(OutputFormat::Gif),
+            Some("svg") => Ok(OutputFormat::Svg),
+            Some("pdf") => Ok(OutputFormat::Pdf),
+            Some("dot") => Ok(OutputFormat::Dot),
+            Some("txt") => Ok(OutputFormat::Txt),
+            Some(format) => Err(format!("Resulted in error in parsing: output format {} is invalid, expected png, jpeg, gif, svg, pdf, dot or txt", format).into())
+        }
+    }
+
+    ///
+    /// A method that builds every sentence of a corpus (as returned by Config::new) and saves it to the output dir
+    /// in the requested format, by the builder of the format: Tree2Plot or Conll2Plot for images, tree_to_dot or
+    /// conll_to_dot for dot, Tree2String or Conll2String for txt. The files are named by the index of the sentence,
+    /// as in get_out_file_as.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// 
+    /// let args = ["PROGRAM_NAME", "d", "Input/conll.txt", "Output/Txt", "txt"].map(|x| x.to_string()).to_vec();
+    /// let sequences = Config::new(&args).unwrap();
+    /// Config::build_all(sequences, &args[3], Config::get_out_format(&args).unwrap()).unwrap();
+    /// assert!(std::path::Path::new("Output/Txt/0.txt").exists());
+    /// ```
+    /// 
+    pub fn build_all(sequences: DataType, out_dir: &str, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+
+        match sequences {
+            DataType::Constituency(sequences) => {
+                for (i, mut constituency) in sequences.into_iter().enumerate() {
+                    let save_to = Config::get_out_file_as(out_dir, &i.to_string(), format);
+                    let mut string2tree: String2Tree = String2StructureBuilder::new();
+                    string2tree.build(&mut constituency)?;
+                    match format {
+                        OutputFormat::Png | OutputFormat::Jpeg | OutputFormat::Gif | OutputFormat::Svg | OutputFormat::Pdf => {
+                            Tree2Plot::new(string2tree.get_structure()).build(&save_to)?
+                        },
+                        OutputFormat::Dot => std::fs::write(&save_to, tree_to_dot(&string2tree.get_structure())?)?,
+                        OutputFormat::Txt => Tree2String::new(string2tree.get_structure()).build(&save_to)?
+                    }
+                }
+            },
+            DataType::Dependency(sequences) => {
+                for (i, mut dependency) in sequences.into_iter().enumerate() {
+                    let save_to = Config::get_out_file_as(out_dir, &i.to_string(), format);
+                    let mut string2conll: String2Conll = String2StructureBuilder::new();
+                    string2conll.build(&mut dependency)?;
+                    match format {
+                        OutputFormat::Png | OutputFormat::Jpeg | OutputFormat::Gif | OutputFormat::Svg | OutputFormat::Pdf => {
+                            Conll2Plot::new(string2conll.get_structure()).build(&save_to)?
+                        },
+                        OutputFormat::Dot => std::fs::write(&save_to, conll_to_dot(&string2conll.get_structure()))?,
+                        OutputFormat::Txt => Conll2String::from(string2conll.get_sentence()).build(&save_to)?
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// A method that builds every sentence of a corpus (as returned by Config::new) without plotting,
+    /// and returns a csv summary of the corpus, with a header line and "statistic,value,count" lines:
+    /// the distribution of sentence lengths (leaves or tokens), of depths (of the deepest leaf or token),
+    /// and the frequencies of labels (inner node labels or deprels). The lines can be saved using Saver.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// use parsed_to_plot::Saver;
+    /// 
+    /// let args = ["PROGRAM_NAME", "c", "Input/constituencies.txt", "Output"].map(|x| x.to_string()).to_vec();
+    /// let report = Config::corpus_report(Config::new(&args).unwrap()).unwrap();
+    /// assert_eq!(report[0], "statistic,value,count");
+    /// report.save_output("Output/report.csv").unwrap();
+    /// ```
+    /// 
+    pub fn corpus_report(sequences: DataType) -> Result<Vec<String>, Box<dyn Error>> {
+
+        let mut lengths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut depths: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut labels: BTreeMap<String, usize> = BTreeMap::new();
+
+        match sequences {
+            DataType::Constituency(sequences) => {
+                for mut constituency in sequences {
+                    let mut string2tree: String2Tree = String2StructureBuilder::new();
+                    string2tree.build(&mut constituency)?;
+                    let tree = string2tree.get_structure();
+                    let root_id = tree.root_node_id().ok_or("tree is empty")?;
+
+                    for node_id in tree.traverse_pre_order_ids(root_id)? {
+                        if !tree.is_leaf(&node_id)? {
+                            *labels.entry(tree.get(&node_id)?.data().clone()).or_insert(0) += 1;
+                        }
+                    }
+                    *lengths.entry(Config::sentence_length(&tree)?).or_insert(0) += 1;
+                    *depths.entry(tree.height() - 1).or_insert(0) += 1;
+                }
+            },
+            DataType::Dependency(sequences) => {
+                for mut dependency in sequences {
+                    let mut string2conll: String2Conll = String2StructureBuilder::new();
+                    string2conll.build(&mut dependency)?;
+                    let tokens = string2conll.get_structure();
+
+                    for token in &tokens {
+                        *labels.entry(token.get_token_deprel()).or_insert(0) += 1;
+                    }
+                    *lengths.entry(tokens.len()).or_insert(0) += 1;
+                    *depths.entry(token_depths(&tokens).into_iter().map(|(_, depth)| depth).max().unwrap_or(0)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut report = vec![REPORT_HEADER.to_string()];
+        report.extend(lengths.iter().map(|(length, count)| format!("length,{},{}", length, count)));
+        report.extend(depths.iter().map(|(depth, count)| format!("depth,{},{}", depth, count)));
+        report.extend(labels.iter().map(|(label, count)| format!("label,{},{}", csv_field(label), count)));
+        Ok(report)
+    }
+
+    ///
+    /// A method that drops the sentences of a corpus (as returned by Config::new) that are longer than max_length,
+    /// so that only readable examples are plotted. The length of a constituency is its number of leaves,
+    /// the length of a dependency is its number of tokens. Sentences that fail to build are dropped as well.
+    /// Every dropped sentence is reported by a Warning::LineSkipped with its index in the corpus and the reason,
+    /// returned with the filtered corpus, as in guard.
+    /// 
+    pub fn filter_by_length(sequences: DataType, max_length: usize) -> (DataType, Vec<Warning>) {
+
+        let mut warnings = Vec::new();
+        let mut keep = |i: usize, length: Result<usize, Box<dyn Error>>| {
+            let reason = match length {
+                Ok(length) if length <= max_length => return true,
+                Ok(length) => format!("length {}, more than {}", length, max_length),
+                Err(e) => format!("failed to build ({})", e)
+            };
+            warnings.push(Warning::LineSkipped(i, reason));
+            false
+        };
+
+        let filtered = match sequences {
+            DataType::Constituency(sequences) => DataType::Constituency(sequences.into_iter().enumerate()
+                .filter(|(i, constituency)| keep(*i, Config::constituency_length(constituency)))
+                .map(|(_, constituency)| constituency)
+                .collect()),
+            DataType::Dependency(sequences) => DataType::Dependency(sequences.into_iter().enumerate()
+                .filter(|(i, dependency)| keep(*i, Config::dependency_length(dependency)))
+                .map(|(_, dependency)| dependency)
+                .collect())
+        };
+        (filtered, warnings)
+    }
+
+    // A helper method that builds a constituency and returns its number of leaves.
+    fn constituency_length(constituency: &str) -> Result<usize, Box<dyn Error>> {
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency.to_string())?;
+        Config::sentence_length(&string2tree.get_structure())
+    }
+
+    // A helper method that builds a dependency and returns its number of tokens.
+    fn dependency_length(dependency: &[String]) -> Result<usize, Box<dyn Error>> {
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency.to_vec())?;
+        Ok(string2conll.get_structure().len())
+    }
+
+    ///
+    /// A method that splits every line of a constituency corpus (as returned by Config::new) on the given delimiter,
+    /// for files that hold several trees per line (like "(S ...) ||| (S ...)" or tab separated trees).
+    /// The trees keep their order, and empty parts (like a trailing delimiter) are dropped.
+    /// Returns an error for a dependency corpus, in which lines are tokens rather than trees.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// 
+    /// let args: Vec<String> = ["PROGRAM_NAME", "c", "Input/constituencies_delimited.txt", "Output"].map(|x| x.to_string()).to_vec();
+    /// let sequences = Config::split_trees(Config::new(&args).unwrap(), "|||").unwrap();
+    /// assert_eq!(Vec::<String>::try_from(sequences).unwrap().len(), 3);
+    /// ```
+    /// 
+    pub fn split_trees(sequences: DataType, delimiter: &str) -> Result<DataType, Box<dyn Error>> {
+        match sequences {
+            DataType::Constituency(sequences) => Ok(DataType::Constituency(sequences.iter()
+                .flat_map(|line| line.split(delimiter))
+                .map(|tree| tree.trim().to_string())
+                .filter(|tree| !tree.is_empty())
+                .collect())),
+            DataType::Dependency(_) => Err("only constituency lines can be split to multiple trees".into())
         }
+    }
 
+    ///
+    /// A method that draws a uniform random sample of n sentences from a corpus (as returned by Config::new),
+    /// for eyeballing a corpus rather than plotting its first sentences. The sampled sentences keep their order
+    /// in the corpus. Given a seed the sample is reproducible, otherwise the seed is taken from the clock.
+    /// If the corpus has n sentences or less, it is returned whole.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// 
+    /// let args: Vec<String> = ["PROGRAM_NAME", "d", "Input/conll.txt", "Output"].map(|x| x.to_string()).to_vec();
+    /// let sequences = Config::new(&args).unwrap();
+    /// let sample = Config::sample(sequences, 1, Some(7));
+    /// assert_eq!(Vec::<Vec<String>>::try_from(sample).unwrap().len(), 1);
+    /// ```
+    /// 
+    pub fn sample(sequences: DataType, n: usize, seed: Option<u64>) -> DataType {
+
+        let seed = seed.unwrap_or_else(|| {
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.as_nanos() as u64).unwrap_or(0)
+        });
+        let pick = |n_total: usize| sample_indices(n_total, n, seed);
+
+        match sequences {
+            DataType::Constituency(sequences) => {
+                let indices = pick(sequences.len());
+                DataType::Constituency(indices.into_iter().map(|i| sequences[i].clone()).collect())
+            },
+            DataType::Dependency(sequences) => {
+                let indices = pick(sequences.len());
+                DataType::Dependency(indices.into_iter().map(|i| sequences[i].clone()).collect())
+            }
+        }
+    }
+
+    ///
+    /// A method that guards a batch run against pathological sentences of a corpus (as returned by Config::new),
+    /// which could hang or exhaust the memory when plotted. A sentence is skipped if it fails to build, if it has more
+    /// than max_size nodes (constituency) or tokens (dependency), if it is deeper than max_depth, or if its heads
+    /// form a cycle (dependency). The size also bounds the dimensions of the figure. Every skipped sentence is reported
+    /// by a Warning::LineSkipped with its index in the corpus and the reason, returned with the guarded corpus.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Config;
+    /// 
+    /// let args: Vec<String> = ["PROGRAM_NAME", "c", "Input/constituencies.txt", "Output"].map(|x| x.to_string()).to_vec();
+    /// let (sequences, warnings) = Config::guard(Config::new(&args).unwrap(), 1000, 50);
+    /// assert!(!Vec::<String>::try_from(sequences).unwrap().is_empty());
+    /// assert!(warnings.is_empty());
+    /// ```
+    /// 
+    pub fn guard(sequences: DataType, max_size: usize, max_depth: usize) -> (DataType, Vec<Warning>) {
+
+        let mut warnings = Vec::new();
+        let mut keep = |i: usize, violation: Option<String>| match violation {
+            Some(reason) => {
+                warnings.push(Warning::LineSkipped(i, reason));
+                false
+            },
+            None => true
+        };
+
+        let guarded = match sequences {
+            DataType::Constituency(sequences) => DataType::Constituency(sequences.into_iter().enumerate()
+                .filter(|(i, constituency)| keep(*i, Config::constituency_violation(constituency, max_size, max_depth)))
+                .map(|(_, constituency)| constituency)
+                .collect()),
+            DataType::Dependency(sequences) => DataType::Dependency(sequences.into_iter().enumerate()
+                .filter(|(i, dependency)| keep(*i, Config::dependency_violation(dependency, max_size, max_depth)))
+                .map(|(_, dependency)| dependency)
+                .collect())
+        };
+        (guarded, warnings)
+    }
+
+    // A helper method that returns the reason to skip a constituency in a guarded batch run, if any.
+    fn constituency_violation(constituency: &str, max_size: usize, max_depth: usize) -> Option<String> {
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        if let Err(e) = string2tree.build(&mut constituency.to_string()) {
+            return Some(format!("failed to build ({})", e));
+        }
+        let tree = string2tree.get_structure();
+        let n_nodes = tree.root_node_id().and_then(|root_id| tree.traverse_pre_order_ids(root_id).ok()).map(|x| x.count()).unwrap_or(0);
+        if n_nodes > max_size {
+            return Some(format!("{} nodes, more than {}", n_nodes, max_size));
+        }
+        let depth = tree.height().saturating_sub(1);
+        if depth > max_depth {
+            return Some(format!("depth {}, more than {}", depth, max_depth));
+        }
+        None
+    }
+
+    // A helper method that returns the reason to skip a dependency in a guarded batch run, if any.
+    fn dependency_violation(dependency: &[String], max_size: usize, max_depth: usize) -> Option<String> {
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        if let Err(e) = string2conll.build(&mut dependency.to_vec()) {
+            return Some(format!("failed to build ({})", e));
+        }
+        let tokens = string2conll.get_structure();
+        if tokens.len() > max_size {
+            return Some(format!("{} tokens, more than {}", tokens.len(), max_size));
+        }
+        if let Some(token) = tokens.iter().find(|token| in_cycle(&tokens, token)) {
+            return Some(format!("the heads of token {} form a cycle", token.get_token_id()));
+        }
+        let depth = token_depths(&tokens).into_iter().map(|(_, depth)| depth).max().unwrap_or(0);
+        if depth > max_depth {
+            return Some(format!("depth {}, more than {}", depth, max_depth));
+        }
+        None
+    }
+
+    // A helper method that returns the length of a constituency sentence, i.e the number of leaves of its tree.
+    fn sentence_length(tree: &Tree<String>) -> Result<usize, Box<dyn Error>> {
+        let root_id = tree.root_node_id().ok_or("tree is empty")?;
+        let mut n_leaves = 0;
+        for node_id in tree.traverse_pre_order_ids(root_id)? {
+            if tree.is_leaf(&node_id)? {
+                n_leaves += 1;
+            }
+        }
+        Ok(n_leaves)
     }
 
 }
@@ -220,7 +872,8 @@ mod tests {
 
     use std::error::Error;
     use super::configure_structures::DataType;
-    use super::Config;
+    use super::{Config, OutputFormat, PlotFormat, InputType, split_conllu, detect_input_type, auto_plot};
+    use crate::Warning;
 
     fn config_test_template(selector: &str, input_path: &str, output_path: &str, additional: Option<&str>) -> Result<DataType, Box<dyn Error>> {
         
@@ -277,9 +930,19 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "there should be 4 arguments supplied: constituency file and output dir, found 5")]
+    #[should_panic(expected = "there should be 4 or 5 arguments supplied: input selector, input file, output dir and an optional output format, found 3")]
     fn invalid_length() {
 
+        let args = ["PROGRAM_NAME", "c", "Input/constituency.txt"].map(|x| x.to_string()).to_vec();
+        if let Err(e) = Config::new(&args) {
+            panic!("{}", e);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Resulted in error in parsing: output format --- is invalid, expected png, jpeg, gif, svg, pdf, dot or txt")]
+    fn invalid_format() {
+
         let selector = "c";
         let sequences = config_test_template(selector, "Input/constituency.txt", "Output", Some("---"));
         if let Err(e) = sequences {
@@ -287,4 +950,245 @@ mod tests {
         }
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn out_format() {
+
+        let sequences = config_test_template("c", "Input/constituencies.txt", "Output/Svg", Some("svg")).unwrap();
+        let n_sequences = Vec::<String>::try_from(sequences.clone()).unwrap().len();
+        Config::build_all(sequences, "Output/Svg", OutputFormat::Svg).unwrap();
+        for i in 0..n_sequences {
+            let svg = std::fs::read_to_string(Config::get_out_file_as("Output/Svg", &i.to_string(), OutputFormat::Svg)).unwrap();
+            assert!(svg.starts_with("<svg"));
+        }
+
+        let args = ["PROGRAM_NAME", "c", "Input/constituencies.txt", "Output"].map(|x| x.to_string()).to_vec();
+        assert_eq!(Config::get_out_format(&args).unwrap(), OutputFormat::Png);
+        assert_eq!(Config::get_out_file_as("Output", "img", OutputFormat::Png), Config::get_out_file("Output", "img"));
+
+        // the images are saved in the format that the plotting modules find by the extension
+        for format in [OutputFormat::Png, OutputFormat::Jpeg, OutputFormat::Gif, OutputFormat::Svg, OutputFormat::Pdf] {
+            assert_eq!(Some(PlotFormat::from_path(&Config::get_out_file_as("Output", "img", format))), format.plot_format());
+        }
+
+        let sequences = config_test_template("d", "Input/conll.txt", "Output/Dot", Some("dot")).unwrap();
+        Config::build_all(sequences, "Output/Dot", OutputFormat::Dot).unwrap();
+        assert!(std::fs::read_to_string("Output/Dot/0.dot").unwrap().starts_with("digraph {"));
+    }
+
+
+    #[test]
+    fn constituency_bom() {
+
+        let sequences = config_test_template("c", "Input/constituencies_bom.txt", "Output", None).unwrap();
+        let sequences = Vec::<String>::try_from(sequences).unwrap();
+        assert!(sequences.iter().all(|x| x.starts_with('(')));
+    }
+
+
+    #[test]
+    fn constituency_trailing_lines() {
+
+        let sequences = config_test_template("c", "Input/constituencies_trailing.txt", "Output", None).unwrap();
+        let sequences = Vec::<String>::try_from(sequences).unwrap();
+        assert_eq!(sequences.len(), 2);
+        assert!(sequences.iter().all(|x| x.ends_with(')')));
+    }
+
+    #[test]
+    fn constituency_crlf() {
+
+        let sequences = config_test_template("c", "Input/constituencies_crlf.txt", "Output", None).unwrap();
+        let sequences = Vec::<String>::try_from(sequences).unwrap();
+        assert_eq!(sequences.len(), 2);
+        assert!(sequences.iter().all(|x| !x.contains('\r')));
+    }
+
+    #[test]
+    fn constituency_gzipped() {
+
+        // a gzipped file with a .txt extension, recognized by its magic bytes
+        let sequences = config_test_template("c", "Input/constituencies_gzipped.txt", "Output", None);
+        #[cfg(feature = "flate2")]
+        {
+            let plain = config_test_template("c", "Input/constituencies.txt", "Output", None).unwrap();
+            assert_eq!(Vec::<String>::try_from(sequences.unwrap()).unwrap(), Vec::<String>::try_from(plain).unwrap());
+        }
+        #[cfg(not(feature = "flate2"))]
+        assert!(sequences.is_err());
+    }
+
+    #[test]
+    fn corpus_report() {
+
+        let sequences = config_test_template("d", "Input/conll.txt", "Output", None).unwrap();
+        let n_sentences = sequences.clone().into_iter().count();
+        let report = Config::corpus_report(sequences).unwrap();
+        assert_eq!(report[0], "statistic,value,count");
+
+        // every sentence is counted once in the lengths and once in the depths
+        for statistic in ["length,", "depth,"] {
+            let total = report.iter().filter(|x| x.starts_with(statistic))
+            .map(|x| x.rsplit(',').next().unwrap().parse::<usize>().unwrap()).sum::<usize>();
+            assert_eq!(total, n_sentences);
+        }
+    }
+
+    #[test]
+    fn into_iter() {
+
+        // one item per sentence, for constituency as for dependency
+        let sequences = config_test_template("c", "Input/constituencies.txt", "Output", None).unwrap();
+        let constituencies = Vec::<String>::try_from(sequences.clone()).unwrap();
+        let items = sequences.into_iter().collect::<Vec<Vec<String>>>();
+        assert_eq!(items.len(), constituencies.len());
+        assert!(items.iter().zip(constituencies.iter()).all(|(item, constituency)| item == &vec![constituency.clone()]));
+
+        let sequences = config_test_template("d", "Input/conll.txt", "Output", None).unwrap();
+        let dependencies = Vec::<Vec<String>>::try_from(sequences.clone()).unwrap();
+        assert_eq!(sequences.into_iter().collect::<Vec<Vec<String>>>(), dependencies);
+    }
+
+    #[test]
+    fn filter_by_length() {
+
+        let sequences = config_test_template("c", "Input/constituencies_trailing.txt", "Output", None).unwrap();
+        let (filtered, warnings) = Config::filter_by_length(sequences, 3);
+        assert_eq!(Vec::<String>::try_from(filtered).unwrap(), vec!["(S (NP (N Time)) (VP (V flies)))"]);
+        assert_eq!(warnings, vec![Warning::LineSkipped(0, "length 5, more than 3".to_string())]);
+
+        // a sentence that fails to build is skipped, the rest are kept
+        let sequences = DataType::Constituency(vec!["(S (0 (1)".to_string(), "(S (0) (1))".to_string()]);
+        let (filtered, warnings) = Config::filter_by_length(sequences, 3);
+        assert_eq!(Vec::<String>::try_from(filtered).unwrap(), vec!["(S (0) (1))"]);
+        assert!(matches!(&warnings[..], [Warning::LineSkipped(0, reason)] if reason.starts_with("failed to build")));
+    }
+
+    #[test]
+    fn split_trees() {
+
+        let sequences = config_test_template("c", "Input/constituencies_delimited.txt", "Output", None).unwrap();
+        let split = Vec::<String>::try_from(Config::split_trees(sequences, "|||").unwrap()).unwrap();
+        assert_eq!(split, vec![
+            "(S (NP (N Time)) (VP (V flies)))",
+            "(S (NP (N People)) (VP (V watch)))",
+            "(36 (9 (3) (3)) (4 (2) (2)))"
+        ]);
+
+        let sequences = DataType::Constituency(vec!["(S (0))\t(S (1))\t".to_string()]);
+        assert_eq!(Vec::<String>::try_from(Config::split_trees(sequences, "\t").unwrap()).unwrap(), vec!["(S (0))", "(S (1))"]);
+
+        let sequences = config_test_template("d", "Input/conll.txt", "Output", None).unwrap();
+        assert!(Config::split_trees(sequences, "|||").is_err());
+    }
+
+    #[test]
+    fn input_type() {
+
+        let constituencies = std::fs::read_to_string("Input/constituencies.txt").unwrap();
+        assert_eq!(detect_input_type(&constituencies).unwrap(), InputType::Constituency);
+        let conll = std::fs::read_to_string("Input/conll_ud.txt").unwrap();
+        assert_eq!(detect_input_type(&conll).unwrap(), InputType::Dependency);
+
+        // a mix of both types is ambiguous
+        let mixed = format!("{}\n{}", constituencies, conll);
+        assert!(detect_input_type(&mixed).unwrap_err().to_string().starts_with("could not detect the input type"));
+        assert!(detect_input_type("\n# only a comment\n").is_err());
+
+        assert_eq!(auto_plot("Input/constituencies.txt", "Output/AutoConstituency").unwrap(), InputType::Constituency);
+        assert!(std::path::Path::new("Output/AutoConstituency/0.png").exists());
+    }
+
+    #[test]
+    fn head() {
+
+        // the first sentences, as read whole
+        for (selector, input_path) in [("c", "Input/constituencies_trailing.txt"), ("d", "Input/conll.txt")] {
+            let args = ["PROGRAM_NAME", selector, input_path, "Output"].map(|x| x.to_string()).to_vec();
+            let sequences = Config::new(&args).unwrap().into_iter().collect::<Vec<Vec<String>>>();
+            assert!(sequences.len() > 1);
+            for n in [0, 1, sequences.len(), sequences.len() + 1] {
+                let head = Config::head(&args, n).unwrap().into_iter().collect::<Vec<Vec<String>>>();
+                assert_eq!(head, sequences[..n.min(sequences.len())].to_vec());
+            }
+        }
+    }
+
+    #[test]
+    fn str_input() {
+
+        // the same sequences as reading the files
+        for (input_type, selector, input_path) in [(InputType::Constituency, "c", "Input/constituencies_bom.txt"), (InputType::Dependency, "d", "Input/conll.txt")] {
+            let sequences = config_test_template(selector, input_path, "Output", None).unwrap();
+            let input = std::fs::read_to_string(input_path).unwrap();
+            let from_str = Config::from_str_input(input_type, &input, "Output").unwrap();
+            assert_eq!(from_str.into_iter().collect::<Vec<Vec<String>>>(), sequences.into_iter().collect::<Vec<Vec<String>>>());
+        }
+
+        let sequences = Config::from_str_input(InputType::Dependency, "\n0\ta\ta\tX\t_\t_\t0\tROOT\t_\t_\n\n", "Output").unwrap();
+        assert_eq!(Vec::<Vec<String>>::try_from(sequences).unwrap().len(), 1);
+        assert!(Config::from_str_input(InputType::Constituency, "", "Output").unwrap().into_iter().next().is_none());
+    }
+
+    #[test]
+    fn split_conllu_string() {
+
+        // the same blocks as reading the file
+        let sequences = config_test_template("d", "Input/conll.txt", "Output", None).unwrap();
+        let input = std::fs::read_to_string("Input/conll.txt").unwrap();
+        assert_eq!(split_conllu(&input), Vec::<Vec<String>>::try_from(sequences).unwrap());
+
+        let blocks = split_conllu("\u{feff}\na\nb\n\nc");
+        assert_eq!(blocks, vec![vec!["a", "b"], vec!["c"]]);
+        assert!(split_conllu("").is_empty());
+
+        // consecutive blank lines make no empty sentences
+        assert_eq!(split_conllu("a\n\n\nb\n\n"), vec![vec!["a"], vec!["b"]]);
+    }
+
+    #[test]
+    fn sample() {
+
+        let sequences = (0..20).map(|i| format!("(S ({}))", i)).collect::<Vec<String>>();
+        let sample = |seed| Vec::<String>::try_from(Config::sample(DataType::Constituency(sequences.clone()), 5, Some(seed))).unwrap();
+        assert_eq!(sample(1), sample(1));
+        assert_eq!(sample(1).len(), 5);
+
+        // the sample keeps the corpus order, and a small corpus is returned whole
+        let positions = sample(2).iter().map(|x| sequences.iter().position(|y| y == x).unwrap()).collect::<Vec<usize>>();
+        assert!(positions.windows(2).all(|x| x[0] < x[1]));
+        assert_eq!(super::sample_indices(3, 5, 0), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn guard() {
+
+        let sequences = DataType::Constituency(vec![
+            "(S (NP (det The) (N people)) (VP (V watch)))".to_string(),
+            "(S (A (B (C (D (E (F x)))))))".to_string(),
+            "(S (a) (b) (c) (d) (e) (f) (g) (h) (i) (j))".to_string()
+        ]);
+        let (guarded, warnings) = Config::guard(sequences, 10, 4);
+        assert_eq!(Vec::<String>::try_from(guarded).unwrap(), vec!["(S (NP (det The) (N people)) (VP (V watch)))"]);
+        assert_eq!(warnings.iter().map(|x| match x {
+            Warning::LineSkipped(i, _) => *i,
+            _ => panic!("unexpected warning {}", x)
+        }).collect::<Vec<usize>>(), vec![1, 2]);
+
+        // malformed lines are skipped rather than stopping the batch
+        let malformed = ["(S (0)) (1 2)", "(S (NP a b))", "((S (a)))", "(S ())", "(S (a)))", "S", ""];
+        let sequences = DataType::Constituency(malformed.iter().map(|x| x.to_string()).collect());
+        let (guarded, warnings) = Config::guard(sequences, 10, 4);
+        assert!(Vec::<String>::try_from(guarded).unwrap().is_empty());
+        assert_eq!(warnings.len(), malformed.len());
+        assert!(warnings.iter().all(|x| x.to_string().contains("failed to build")));
+
+        let sequences = DataType::Dependency(vec![
+            vec!["0	The	the	DET	_	_	1	det	_	_", "1	people	people	NOUN	_	_	1	ROOT	_	_"],
+            vec!["0	a	a	X	_	_	1	dep	_	_", "1	b	b	X	_	_	2	dep	_	_", "2	c	c	X	_	_	1	dep	_	_"]
+        ].into_iter().map(|x| x.into_iter().map(|y| y.to_string()).collect()).collect());
+        let guarded = Vec::<Vec<String>>::try_from(Config::guard(sequences, 10, 4).0).unwrap();
+        assert_eq!(guarded.len(), 1);
+        assert_eq!(guarded[0][1], "1	people	people	NOUN	_	_	1	ROOT	_	_");
+    }
+
+}
@@ -3,16 +3,82 @@
 // Under MIT license
 //
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
 use plotters::{prelude::*, style::text_anchor::{Pos, HPos, VPos}};
-use super::string_2_conll::Token;
+use super::string_2_conll::{Token, Sentence, VIRTUAL_ROOT_HEAD};
+use plotters::coord::Shift;
 use super::generic_enums::{Element, Accumulator};
+use super::output_format::{BitmapPlotBackend, EncodingOptions, PlotFormat, PngCompression, OverlayChart, ChartOverlay, OverlayBackend, save_bitmap, save_pdf, validate_font, validate_png_keyword};
+use super::warnings::Warning;
+use super::text_layout::{fit_label, overflows, estimate_text_width};
+use super::conll_analysis::{misc_value, parse_agreement};
 use super::generic_traits::generic_traits::{Structure2PlotBuilder, Structure2PlotPlotter, WalkActions, WalkTree};
 
 const DIM_CONST: u32 = 640;
+const MIN_DIM: u32 = 100;
 const MARGIN: u32 = 15;
 const FONT_SIZE: f32 = 15.0;
 const FONT_CONST: f32 = 7.5 / 5.0;
+const LEGEND_PAD: u32 = 10;
+const MAX_ARC_WIDTH: f32 = 5.0;  // the line width of the arc with the highest weight, see set_weight_key
+const X_LABEL_AREA: u32 = 10;
+const Y_LABEL_AREA: u32 = 50;
+const FLAT_TOKEN_WIDTH: u32 = 80;  // the width of a token in pixels when the arcs are not drawn, see set_show_arcs
+const FLAT_ROW_HEIGHT: u32 = 30;  // the height of a row in pixels when the arcs are not drawn
+const AGREEMENT_COLOR: RGBColor = RGBColor(0, 150, 0);  // the color of arcs that agree with the gold parse, see set_gold
+const DISAGREEMENT_COLOR: RGBColor = RGBColor(220, 0, 0);  // the color of arcs that do not agree with the gold parse
+
+// A user predicate that optionally returns the rgb color of a token, see set_token_color.
+type TokenColor = Arc<dyn Fn(&Token) -> Option<(u8, u8, u8)> + Send + Sync>;
+
+// A user callback that returns the texts of the form row and the pos row of a token, see set_token_label_format.
+type TokenLabelFormat = Arc<dyn Fn(&Token) -> (String, String) + Send + Sync>;
+
+/// An enum of the token categories that can be used to color a dependency plot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorBy {
+    /// arcs are colored by the deprel of the dependent
+    Deprel,
+    /// arcs and token rows are colored by the pos of the dependent
+    Pos
+}
+
+/// An enum of the conll columns that can fill the two text rows of a dependency plot, see set_row_fields.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenField {
+    /// the form of the token (the default of the bottom row)
+    Form,
+    /// the lemma of the token
+    Lemma,
+    /// the universal pos tag of the token (the default of the top row)
+    Pos,
+    /// the language specific pos tag of the token
+    Xpos,
+    /// the morphological features of the token
+    Feats,
+    /// the deprel of the token
+    Deprel,
+    /// the misc field of the token
+    Misc
+}
+
+impl TokenField {
+
+    // A method that returns the value of the field in the given token.
+    fn of(&self, token: &Token) -> String {
+        match self {
+            TokenField::Form => token.get_token_form(),
+            TokenField::Lemma => token.get_token_lemma(),
+            TokenField::Pos => token.get_token_pos(),
+            TokenField::Xpos => token.get_token_xpos(),
+            TokenField::Feats => token.get_token_feats(),
+            TokenField::Deprel => token.get_token_deprel(),
+            TokenField::Misc => token.get_token_misc()
+        }
+    }
+}
 
 /// A struct that wraps the needed fields to plot a token
 #[derive(Clone, Debug)]
@@ -22,7 +88,8 @@ pub(in crate) struct ConllPlotData {
     deprel: String,             // to be written above an arrow
     pos: String,                // to be written on line 1
     form: String,               // to be written on line 0
-    height: f32                 // height of arrow
+    height: f32,                // height of arrow
+    virtual_root: bool          // attached to a virtual root, drawn as a vertical arc from above
 }
 
 // A struct that wraps the needed fields to compute location and plot Vec<token>
@@ -35,72 +102,543 @@ pub(in crate) struct WalkData {
 
 /// A Conll2Plot struct, mainly holds the tokens vector. This type will implement Structure2PlotBuilder, Structure2PlotPlotter,
 /// WalkTree and WalkActions, with an ultimate goal of saving a plot of the dependency to file.
+/// 
+/// The root is the token whose head is its own id, at most one root is supported. As in UD, the root can also be
+/// a token whose head is 0 while 0 is not a token id (the ids start at 1), i.e attached to a virtual root, then
+/// its arc comes straight down from above all other arcs. A token whose head does not match any token id otherwise
+/// is unattached, this covers a head of -1 or "_". Unattached tokens (and their dependents) are drawn like the root, with no arc.
+/// A configured Conll2Plot can be cloned to plot variants of the same dependency, the clones share the callbacks.
+#[derive(Clone)]
 pub struct Conll2Plot {
     tokens: Vec<Token>,
-    y_shift: f32 // room for pos and form
+    id2position: HashMap<u32, usize>, // ids may be gapped, map them to contiguous x positions
+    y_shift: f32, // room for pos and form
+    show_pos: bool,
+    arrow_to_head: bool,
+    warnings: Vec<Warning>,
+    margin: u32,
+    truncate_labels: bool,
+    color_by: Option<ColorBy>,
+    show_legend: bool,
+    arrow_size: Option<u32>,
+    background: RGBColor,
+    foreground: RGBColor,
+    scale: f32,
+    encoding: EncodingOptions,
+    token_color: Option<TokenColor>,
+    show_arc_length: bool,
+    token_label_format: Option<TokenLabelFormat>,
+    weight_key: Option<String>,
+    abbreviations: HashMap<String, String>,
+    compact_layout: bool,
+    show_arcs: bool,
+    gold: Option<Vec<Token>>,
+    row_fields: (TokenField, TokenField),
+    overlay: Option<ChartOverlay>
+}
+
+impl Conll2Plot {
+
+    ///
+    /// Initialization of a Conll2Plot object from parallel arrays of words, pos tags, heads and deprels,
+    /// as commonly given by parsers. Ids are given by the position in the arrays (starting from 0),
+    /// the other conll fields are filled with "_". Returns an error if the arrays differ in length.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Conll2Plot;
+    /// 
+    /// let words = ["The", "people", "watch", "the", "game"];
+    /// let pos = ["DET", "NOUN", "VERB", "DET", "NOUN"];
+    /// let heads = [1.0, 2.0, 2.0, 4.0, 2.0];
+    /// let deprels = ["det", "nsubj", "ROOT", "det", "dobj"];
+    /// 
+    /// let _conll2plot = Conll2Plot::from_arrays(&words, &pos, &heads, &deprels).unwrap();
+    /// ```
+    /// 
+    pub fn from_arrays<S: AsRef<str>>(words: &[S], pos: &[S], heads: &[f32], deprels: &[S]) -> Result<Self, Box<dyn Error>> {
+
+        let n = words.len();
+        if pos.len() != n || heads.len() != n || deprels.len() != n {
+            return Err(format!("arrays differ in length: words {}, pos {}, heads {}, deprels {}", n, pos.len(), heads.len(), deprels.len()).into());
+        }
+
+        let tokens = (0..n).map(|i| {
+            Token::from_fields(i as f32, words[i].as_ref(), pos[i].as_ref(), heads[i], deprels[i].as_ref())
+        }).collect::<Vec<Token>>();
+
+        Ok(Structure2PlotBuilder::new(tokens))
+    }
+
+    ///
+    /// A set method to determine the conll columns of the two text rows of every token, the bottom row and the top row
+    /// (default TokenField::Form and TokenField::Pos), for example the lemma and the xpos. Coloring by pos (see set_color_by)
+    /// still uses the pos tags. A custom format (see set_token_label_format) overrides the rows.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::{Conll2Plot, TokenField};
+    /// 
+    /// let words = ["The", "people", "watch", "the", "game"];
+    /// let pos = ["DET", "NOUN", "VERB", "DET", "NOUN"];
+    /// let heads = [1.0, 2.0, 2.0, 4.0, 2.0];
+    /// let deprels = ["det", "nsubj", "ROOT", "det", "dobj"];
+    /// 
+    /// let mut conll2plot = Conll2Plot::from_arrays(&words, &pos, &heads, &deprels).unwrap();
+    /// conll2plot.set_row_fields(TokenField::Form, TokenField::Deprel);
+    /// ```
+    /// 
+    pub fn set_row_fields(&mut self, bottom_row: TokenField, top_row: TokenField) {
+        self.row_fields = (bottom_row, top_row);
+    }
+
+    ///
+    /// A set method to determine whether the pos row is drawn under the arcs (default true).
+    /// When set to false, only forms and arcs are drawn, and the space of the pos row is reclaimed.
+    /// 
+    pub fn set_show_pos(&mut self, show_pos: bool) {
+        self.show_pos = show_pos;
+        self.y_shift = if show_pos { 2.0 } else { 1.0 };
+    }
+
+    ///
+    /// A set method to determine the direction convention of the arcs (default false).
+    /// By default arrowheads point at the dependent, when set to true they point at the head instead.
+    /// 
+    pub fn set_arrow_to_head(&mut self, arrow_to_head: bool) {
+        self.arrow_to_head = arrow_to_head;
+    }
+
+    ///
+    /// A set method to determine whether the heights of the arcs are reassigned to reduce the height of the plot (default false).
+    /// By default every arc is placed above all the arcs drawn before it in its span, in the order of the walk. In the compact
+    /// layout the arcs are placed from the shortest, every arc above the arcs nested in it, at the lowest height that no
+    /// arc crossing it takes. Heights are reused once the arcs that took them end, which makes non projective parses
+    /// shorter and denser. Projective parses are already as low as possible, and are not changed.
+    /// 
+    pub fn set_compact_layout(&mut self, compact_layout: bool) {
+        self.compact_layout = compact_layout;
+    }
+
+    ///
+    /// A set method to determine whether the arcs are drawn (default true). Without arcs only the rows of the tokens are
+    /// drawn, as a flat strip of forms and pos tags. The strip is sized by the number of tokens (a fixed width for every
+    /// token) rather than by the arcs, and the font size is kept, which makes it the fastest rendering of long sentences.
+    /// 
+    pub fn set_show_arcs(&mut self, show_arcs: bool) {
+        self.show_arcs = show_arcs;
+    }
+
+    ///
+    /// A set method to determine a gold parse of the same sentence, to which the plotted parse is compared (default None).
+    /// Every arc (and its deprel) is colored by the agreement of its dependent with the gold token of the same id, green
+    /// if both the head and the deprel are the same, red otherwise, see parse_agreement. The rows keep their colors.
+    /// Returns an error if the gold parse does not have the same ids with the same forms.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut gold = vec!["0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_".to_string(), "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_".to_string()];
+    /// let mut predicted = vec!["0\tThe\tthe\tDET\t_\t_\t1\tamod\t_\t_".to_string(), "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_".to_string()];
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut gold).unwrap();
+    /// let gold = string2conll.get_structure();
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut predicted).unwrap();
+    /// 
+    /// let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+    /// conll2plot.set_gold(Some(gold)).unwrap();
+    /// conll2plot.build("Output/dependency_gold.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_gold(&mut self, gold: Option<Vec<Token>>) -> Result<(), Box<dyn Error>> {
+        if let Some(gold) = &gold {
+            parse_agreement(gold, &self.tokens)?;
+        }
+        self.gold = gold;
+        Ok(())
+    }
+
+    ///
+    /// A set method to determine whether the length of every arc, the distance between the head and the dependent
+    /// in token positions, is appended to its deprel, "nsubj (1)" (default false). Useful to spot long-range dependencies.
+    /// 
+    pub fn set_show_arc_length(&mut self, show_arc_length: bool) {
+        self.show_arc_length = show_arc_length;
+    }
+
+    ///
+    /// A set method to determine the size of the arrowheads in pixels, i.e the horizontal and vertical extent
+    /// of each of their sides (default None, the size follows the font size and scales with the figure).
+    /// 
+    pub fn set_arrow_size(&mut self, arrow_size: Option<u32>) {
+        self.arrow_size = arrow_size;
+    }
+
+    ///
+    /// A set method to determine the margin around the plot area, in pixels (default 15).
+    /// Increase it if labels near the edges of the image get clipped.
+    /// 
+    pub fn set_margin(&mut self, margin: u32) {
+        self.margin = margin;
+    }
+
+    ///
+    /// A set method to determine whether forms and pos tags wider than the width of a token are truncated
+    /// with an ellipsis (default false). Without truncation, long words of adjacent tokens may overlap.
+    /// 
+    pub fn set_truncate_labels(&mut self, truncate_labels: bool) {
+        self.truncate_labels = truncate_labels;
+    }
+
+    ///
+    /// A set method to determine the category by which tokens are colored (default None, all black).
+    /// Every category gets a distinct color, by order of appearance in the tokens.
+    /// 
+    pub fn set_color_by(&mut self, color_by: Option<ColorBy>) {
+        self.color_by = color_by;
+    }
+
+    ///
+    /// A set method to determine a user predicate that colors specific tokens (by default none is used). When the predicate
+    /// returns an rgb value for a token, it overrides the color of the token's form, pos, arc and deprel,
+    /// on top of the colors of set_color_by and set_foreground_color. Tokens for which it returns None are unaffected.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+    ///     "1\tpeople\tpeople\tNOUN\t_\t_\t2\tnsubj\t_\t_",
+    ///     "2\twatch\twatch\tVERB\t_\t_\t2\tROOT\t_\t_"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut dependency).unwrap();
+    /// 
+    /// let stoplist = ["the", "a"];
+    /// let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+    /// conll2plot.set_token_color(move |token| match stoplist.contains(&token.get_token_lemma().as_str()) {
+    ///     true => Some((150, 150, 150)),
+    ///     false => None
+    /// });
+    /// conll2plot.build("Output/dependency_stoplist.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_token_color<F>(&mut self, token_color: F)
+    where F: Fn(&Token) -> Option<(u8, u8, u8)> + Send + Sync + 'static {
+        self.token_color = Some(Arc::new(token_color));
+    }
+
+    ///
+    /// A set method to determine a callback that returns the texts written in the form row and in the pos row
+    /// of every token (by default the form and the pos), for example "form (lemma)" or "pos:feats".
+    /// The tokens and the layout are not changed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+    ///     "1\tpeople\tpeople\tNOUN\t_\tNumber=Plur\t2\tnsubj\t_\t_",
+    ///     "2\twatch\twatch\tVERB\t_\t_\t2\tROOT\t_\t_"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut dependency).unwrap();
+    /// 
+    /// let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+    /// conll2plot.set_token_label_format(|token| (
+    ///     format!("{} ({})", token.get_token_form(), token.get_token_lemma()),
+    ///     format!("{}:{}", token.get_token_pos(), token.get_token_feats())
+    /// ));
+    /// conll2plot.build("Output/dependency_label_format.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_token_label_format<F>(&mut self, token_label_format: F)
+    where F: Fn(&Token) -> (String, String) + Send + Sync + 'static {
+        self.token_label_format = Some(Arc::new(token_label_format));
+    }
+
+    ///
+    /// A set method to determine a callback that draws additional series over the plot (by default nothing is drawn),
+    /// for example a mark over a token. The callback gets the chart after the dependencies are drawn and before the
+    /// image is saved. Every token occupies one unit on the x axis (the first token is around 0), and the y axis spans
+    /// 0 to 10 from bottom to top. See Tree2Plot::set_overlay for an example.
+    /// 
+    pub fn set_overlay<F>(&mut self, overlay: F)
+    where F: Fn(OverlayChart) -> Result<(), Box<dyn Error>> + Send + Sync + 'static {
+        self.overlay = Some(Arc::new(overlay));
+    }
+
+    ///
+    /// A set method to determine a key in the misc field of the tokens whose numeric value is the weight of their arc,
+    /// for example the confidence of the parser in the attachment (by default None, all arcs are drawn in the same width).
+    /// The line width of every arc is proportional to its weight, the arc with the highest weight is drawn 5 times wider
+    /// than the default. Tokens without a valid (non negative) value for the key are drawn in the default width.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\tConfidence=0.98",
+    ///     "1\tpeople\tpeople\tNOUN\t_\t_\t2\tnsubj\t_\tConfidence=0.6",
+    ///     "2\twatch\twatch\tVERB\t_\t_\t2\tROOT\t_\t_",
+    ///     "3\tthe\tthe\tDET\t_\t_\t4\tdet\t_\tConfidence=0.95",
+    ///     "4\tgame\tgame\tNOUN\t_\t_\t2\tdobj\t_\tConfidence=0.3"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut dependency).unwrap();
+    /// 
+    /// let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+    /// conll2plot.set_weight_key(Some("Confidence"));
+    /// conll2plot.build("Output/dependency_weights.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_weight_key(&mut self, weight_key: Option<&str>) {
+        self.weight_key = weight_key.map(|x| x.to_string());
+    }
+
+    ///
+    /// A set method to determine a table of abbreviations of pos tags and deprels, for example "PROPN" to "PN", to keep
+    /// the plot compact (empty by default). The abbreviations are only drawn, the tokens are not changed. Tags that are
+    /// not in the table are drawn as they are, and so are the pos tags of a custom format (see set_token_label_format).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::Conll2Plot;
+    /// use std::collections::HashMap;
+    /// 
+    /// let words = ["John", "left", "yesterday"];
+    /// let pos = ["PROPN", "VERB", "NOUN"];
+    /// let heads = [1.0, 1.0, 1.0];
+    /// let deprels = ["nsubj", "root", "obl:tmod"];
+    /// 
+    /// let mut conll2plot = Conll2Plot::from_arrays(&words, &pos, &heads, &deprels).unwrap();
+    /// let abbreviations = [("PROPN", "PN"), ("obl:tmod", "tmod")];
+    /// conll2plot.set_abbreviations(abbreviations.iter().map(|(x, y)| (x.to_string(), y.to_string())).collect::<HashMap<String, String>>());
+    /// ```
+    /// 
+    pub fn set_abbreviations(&mut self, abbreviations: HashMap<String, String>) {
+        self.abbreviations = abbreviations;
+    }
+
+    ///
+    /// A set method to determine whether a legend that maps the colors to the categories is drawn
+    /// in the top right corner of the plot (default false). Relevant only when set_color_by is used.
+    /// 
+    pub fn set_show_legend(&mut self, show_legend: bool) {
+        self.show_legend = show_legend;
+    }
+
+    ///
+    /// A set method to determine the background color of the figure as an rgb value (default white).
+    /// 
+    pub fn set_background_color(&mut self, rgb: (u8, u8, u8)) {
+        self.background = RGBColor(rgb.0, rgb.1, rgb.2);
+    }
+
+    ///
+    /// A set method to determine the color of the labels and arcs as an rgb value (default black).
+    /// Tokens colored by category (see set_color_by) keep their category color.
+    /// 
+    pub fn set_foreground_color(&mut self, rgb: (u8, u8, u8)) {
+        self.foreground = RGBColor(rgb.0, rgb.1, rgb.2);
+    }
+
+    ///
+    /// A set method to determine a uniform scale factor of the figure (default 1.0), for high resolution images.
+    /// The computed dimensions, font size, margins, arrowheads and line widths are all multiplied by the factor,
+    /// so the layout is kept as in the default figure. Returns an error if the factor is not a positive number.
+    /// 
+    pub fn set_scale(&mut self, scale: f32) -> Result<(), Box<dyn Error>> {
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(format!("the scale should be a positive number, got {}", scale).into());
+        }
+        self.scale = scale;
+        Ok(())
+    }
+
+    ///
+    /// A set method to determine the compression level of png outputs (default PngCompression::Fast).
+    /// Higher levels produce smaller files at the cost of slower encoding, useful for large batch runs.
+    /// 
+    pub fn set_png_compression(&mut self, png_compression: PngCompression) {
+        self.encoding.png_compression = png_compression;
+    }
+
+    ///
+    /// A set method to determine the quality of jpeg outputs (saved to .jpg or .jpeg paths), in the range 1-100 (default 75).
+    /// 
+    pub fn set_jpeg_quality(&mut self, jpeg_quality: u8) {
+        self.encoding.jpeg_quality = jpeg_quality;
+    }
+
+    ///
+    /// A set method to determine whether bitmap outputs (png, jpeg, gif and other bitmap formats) are anti-aliased (default true).
+    /// Without anti-aliasing every pixel is drawn in one of the colors of the plot, so the same plot produces the same image
+    /// on every platform, useful for image regression tests. Svg and pdf outputs are not affected.
+    /// 
+    pub fn set_antialiasing(&mut self, antialiasing: bool) {
+        self.encoding.antialiasing = antialiasing;
+    }
+
+    ///
+    /// A set method to determine a font that is embedded in bitmap outputs (png, jpeg, gif and other bitmap formats)
+    /// for all texts, as the bytes of a TrueType or OpenType font file (default None, the sans-serif font of the system).
+    /// The system font differs across machines, an embedded font (e.g. by include_bytes) gives the same text everywhere.
+    /// A public domain font is bundled with the bundled_font feature, as BUNDLED_FONT. Svg and pdf outputs are not affected.
+    /// Returns an error if the bytes are not a valid font.
+    /// 
+    pub fn set_font(&mut self, font: Option<&'static [u8]>) -> Result<(), Box<dyn Error>> {
+        if let Some(font) = font {
+            validate_font(font)?;
+        }
+        self.encoding.font = font;
+        Ok(())
+    }
+
+    ///
+    /// A method to compute the dimensions in pixels (width, height) of the image that build would save, without drawing
+    /// or writing anything. Useful to lay out several figures, or to check their sizes before saving them.
+    /// 
+    pub fn compute_dimensions(&self) -> Result<(u32, u32), Box<dyn Error>> {
+        let (_, arcs_height) = self.layout()?;
+        let (fig_dims, _, _, _) = self.figure_dimensions(arcs_height);
+        Ok(fig_dims)
+    }
+
+    ///
+    /// A method to add a text entry to the metadata of png outputs, for example the sent_id or the text of the sentence,
+    /// so the image is self-describing. Every call adds an entry, saved as a text chunk of the png (iTXt). The keyword
+    /// should be 1-79 printable ascii characters, otherwise an error is returned and the entry is not added. Other output
+    /// formats are not affected.
+    /// See Tree2Plot::add_png_text for an example.
+    /// 
+    pub fn add_png_text(&mut self, keyword: &str, text: &str) -> Result<(), Box<dyn Error>> {
+        validate_png_keyword(keyword)?;
+        self.encoding.png_texts.push((keyword.to_string(), text.to_string()));
+        Ok(())
+    }
+
+    ///
+    /// A get method to retrieve the warnings accumulated during the last build.
+    /// 
+    pub fn get_warnings(&self) -> Vec<Warning> {
+        self.warnings.clone()
+    }
+
+    ///
+    /// A get method to retrieve the tokens of the plotted dependency, available right after initialization.
+    /// 
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens
+    }
 }
 
 
+impl From<Sentence> for Conll2Plot {
+    fn from(sentence: Sentence) -> Self {
+        Structure2PlotBuilder::new(Vec::<Token>::from(sentence))
+    }
+}
+
 impl Structure2PlotBuilder<Vec<Token>> for Conll2Plot {
 
     fn new(structure: Vec<Token>) -> Self {
+
+        // map every token id to its position among the sorted ids (ids are keyed by bits since f32 is not hashable)
+        let mut ids = structure.iter().map(|token| token.get_token_id()).collect::<Vec<f32>>();
+        ids.sort_by(|x, y| x.partial_cmp(y).expect("token ids should be comparable"));
+        let id2position = ids.iter().enumerate().map(|(i, id)| (id.to_bits(), i)).collect::<HashMap<u32, usize>>();
         
         Self {
             tokens: structure,
-            y_shift: 2.0        // this constant means two vertical lines are saved for pos and form
+            id2position,
+            y_shift: 2.0,       // this constant means two vertical lines are saved for pos and form
+            show_pos: true,
+            arrow_to_head: false,
+            warnings: Vec::new(),
+            margin: MARGIN,
+            truncate_labels: false,
+            color_by: None,
+            show_legend: false,
+            arrow_size: None,
+            background: WHITE,
+            foreground: BLACK,
+            scale: 1.0,
+            encoding: EncodingOptions::default(),
+            token_color: None,
+            show_arc_length: false,
+            token_label_format: None,
+            weight_key: None,
+            abbreviations: HashMap::new(),
+            compact_layout: false,
+            show_arcs: true,
+            gold: None,
+            row_fields: (TokenField::Form, TokenField::Pos),
+            overlay: None
         }
     }
 
     /// See examples on how to use this function on lib.rs
     fn build(&mut self, save_to: &str) -> Result<(), Box<dyn Error>> {
 
-        // extraction of the plotting data through recursion
-        let walk_args: Vec<[f32; 2]> = vec![[0.0, 0.0]; (&self.tokens).len()];
-        let plot_data_vec: Vec<ConllPlotData> = Vec::new();
-        let walk_data: WalkData = WalkData { conll_plot_data: plot_data_vec, walk_args: walk_args };
-        let mut accumulator = Accumulator::WD(walk_data);
-        self.walk(None, &mut accumulator)?;
-
-        // return to walk data from the general enum accumulator
-        let walk_data = <&mut WalkData>::try_from(&mut accumulator)?;
-
-        // determine general plot settings for the dependency
-        let seq_length = (&self.tokens).len() as f32;
-        let built_height = self.y_shift + (&walk_data).walk_args[0..seq_length as usize].concat().iter().map(|x| *x as usize).max().unwrap() as f32;
-        let total_units = 2*DIM_CONST / (seq_length + built_height) as u32;
-        let width = total_units * seq_length as u32;
-        let height = total_units * built_height as u32;
-        let fig_dims: (u32, u32) = (width, height);
-
-        // calculate dynamic font size
-        let font_size = (FONT_CONST * (height as f32 / width as f32) * FONT_SIZE) as i32;
+        self.warnings.clear();
+        let (plot_data_vec, arcs_height) = self.layout()?;
+        let (fig_dims, font_size, x_pad, clamped) = self.figure_dimensions(arcs_height);
+        self.warnings.extend(clamped);
         let font_style = ("sans-serif", font_size);
 
-        // initialization of backend settings
-        let root_area = BitMapBackend::new(save_to, fig_dims)
-        .into_drawing_area();
-        root_area.fill(&WHITE).unwrap();
-        let x_spec = std::ops::Range{start: -0.1 as f32, end: seq_length};
-        let y_spec = std::ops::Range{start: 0.0 as f32, end: 10.0 as f32};
-
-        let mut chart = ChartBuilder::on(&root_area)
-        .margin(MARGIN)
-        .x_label_area_size(10)
-        .y_label_area_size(50)
-        .build_cartesian_2d(x_spec, y_spec).unwrap();
-
-        chart
-        .configure_mesh()
-        .disable_x_mesh()
-        .disable_y_mesh()
-        .disable_x_axis()
-        .disable_y_axis()
-        .draw()
-        .unwrap();
-
-        self.plot(&mut chart, walk_data.conll_plot_data.clone(), font_style)?;
-        
-        Ok(())
+        // initialization of the backend by the extension of save_to (png by default, jpeg, svg or pdf)
+        let seq_length = self.tokens.len() as f32;
+        let x_spec = std::ops::Range{start: -0.1 - x_pad, end: seq_length + x_pad};
+        match PlotFormat::from_path(save_to) {
+            format @ (PlotFormat::Png | PlotFormat::Jpeg) => {
+                let mut buffer = vec![0; (fig_dims.0 * fig_dims.1 * 3) as usize];
+                let backend = BitMapBackend::with_buffer(&mut buffer, fig_dims);
+                self.draw(BitmapPlotBackend::new(backend, &self.encoding)?.into_drawing_area(), plot_data_vec, x_spec, font_style)?;
+                save_bitmap(&buffer, fig_dims, save_to, &format, &self.encoding)
+            },
+            // a dependency plot is not animated, it is saved as a single frame
+            format @ (PlotFormat::Gif | PlotFormat::Bitmap) => {
+                let backend = match format {
+                    PlotFormat::Gif => BitMapBackend::gif(save_to, fig_dims, self.encoding.gif_frame_delay)?,
+                    _ => BitMapBackend::new(save_to, fig_dims)
+                };
+                self.draw(BitmapPlotBackend::new(backend, &self.encoding)?.into_drawing_area(), plot_data_vec, x_spec, font_style)
+            },
+            PlotFormat::Svg => self.draw(SVGBackend::new(save_to, fig_dims).into_drawing_area(), plot_data_vec, x_spec, font_style),
+            PlotFormat::Pdf => {
+                let mut svg = String::new();
+                self.draw(SVGBackend::with_string(&mut svg, fig_dims).into_drawing_area(), plot_data_vec, x_spec, font_style)?;
+                save_pdf(&svg, save_to)
+            }
+        }
     }
 
 }
@@ -114,35 +652,128 @@ impl Structure2PlotPlotter<ConllPlotData> for Conll2Plot {
         let text_style = TextStyle::from(font_style)
         .transform(FontTransform::None)
         .font.into_font().style(FontStyle::Bold)
-        .with_color(&BLACK)
+        .with_color(&self.foreground)
         .with_anchor::<RGBColor>(Pos::new(HPos::Center, VPos::Center))
         .into_text_style(chart.plotting_area());
 
-        let text_draw = |x, y, label: String| {
+        let text_draw = |x, y, label: String, style| {
             return EmptyElement::at((x,y))
-            + Text::new(format!("{}", label), (0,0), &text_style
-            );
+            + Text::new(label, (0,0), style);
         };
 
-        for plot_data in plot_data_vec {
+        // every token occupies one unit on the x axis, that is the width that a form or pos can take
+        let (token_width, unit_height) = Conll2Plot::unit_pixels(chart);
+
+        // the arrowheads are sized in pixels and converted to the units of each axis
+        let arrow_size = self.arrow_size.map(|x| self.scaled(x) as f32).unwrap_or(font_style.1 as f32);
+        let (arrow_dx, arrow_dy) = (arrow_size / token_width, arrow_size / unit_height);
+        let row_label = |label: String| {
+            match self.truncate_labels {
+                true => fit_label(&label, token_width, font_style.1),
+                false => label
+            }
+        };
+
+        // the colors of the tokens are determined in advance, black unless colored by category
+        let category_colors = self.category_colors();
+        // the rows may hold other columns (see set_row_fields), the pos is taken from the token
+        let category_colors_of = plot_data_vec.iter().map(|plot_data| {
+            let pos = self.token_at(plot_data.end as usize)?.map(|token| token.get_token_pos()).unwrap_or_default();
+            Ok(match self.category_of(&plot_data.deprel, &pos) {
+                Some(category) => category_colors.iter().find(|(x, _)| x == category).map(|(_, color)| *color).unwrap_or(self.foreground),
+                None => self.foreground
+            })
+        }).collect::<Result<Vec<RGBColor>, Box<dyn Error>>>()?;
+
+        // a color of the user predicate overrides both the arc and the row of the token (the token is plotted at end)
+        let overrides = plot_data_vec.iter().map(|plot_data| self.token_color_at(plot_data.end as usize))
+        .collect::<Result<Vec<Option<RGBColor>>, Box<dyn Error>>>()?;
+        let colors = category_colors_of.iter().zip(overrides.iter()).map(|(color, token_color)| token_color.unwrap_or(*color))
+        .collect::<Vec<RGBColor>>();
+        let row_colors = match self.color_by {
+            Some(ColorBy::Pos) => colors.clone(),
+            _ => overrides.iter().map(|token_color| token_color.unwrap_or(self.foreground)).collect::<Vec<RGBColor>>()
+        };
+
+        // arcs compared to a gold parse are colored by their agreement instead
+        let colors = match &self.gold {
+            Some(gold) => {
+                let agreement = parse_agreement(gold, &self.tokens)?;
+                plot_data_vec.iter().zip(colors.iter()).map(|(plot_data, color)| {
+                    let token = self.token_at(plot_data.end as usize)?;
+                    let agrees = token.and_then(|token| self.tokens.iter().position(|x| x.get_token_id() == token.get_token_id())).map(|i| agreement[i]);
+                    Ok(match agrees {
+                        Some(true) => AGREEMENT_COLOR,
+                        Some(false) => DISAGREEMENT_COLOR,
+                        None => *color
+                    })
+                }).collect::<Result<Vec<RGBColor>, Box<dyn Error>>>()?
+            },
+            None => colors
+        };
+        let arc_styles = colors.iter().map(|color| text_style.color(color)).collect::<Vec<TextStyle>>();
+        let row_styles = row_colors.iter().map(|color| text_style.color(color)).collect::<Vec<TextStyle>>();
+
+        let arc_widths = self.arc_widths(&plot_data_vec)?;
+        for (i, plot_data) in plot_data_vec.into_iter().enumerate() {
+
+            let line_style = colors[i].stroke_width(arc_widths[i]);
+
+            // a flat strip has only the rows of the tokens
+            if self.show_arcs && plot_data.virtual_root {
 
-            if plot_data.height >= 0.0 {
+                // a vertical arc, the arrowhead is on the dependent (bottom) or on the virtual root (top) if requested
+                let (x, y_shift, epsilon) = (plot_data.end, self.y_shift, 0.2);
+                let top = y_shift + plot_data.height - 2.0 * epsilon;
+                chart.draw_series(LineSeries::new(vec![(x, y_shift), (x, top)], line_style)).unwrap();
+                let (arrow_y, arrow_dy) = if self.arrow_to_head { (top, -arrow_dy) } else { (y_shift, arrow_dy) };
+                chart.draw_series(LineSeries::new(vec![(x, arrow_y), (x + arrow_dx, arrow_y + arrow_dy)], line_style)).unwrap();
+                chart.draw_series(LineSeries::new(vec![(x, arrow_y), (x - arrow_dx, arrow_y + arrow_dy)], line_style)).unwrap();
+                chart.plotting_area().draw(&text_draw(x, y_shift + plot_data.height - epsilon, self.arc_label(&plot_data), &arc_styles[i])).unwrap();
+
+            } else if self.show_arcs && plot_data.height >= 0.0 {
 
                 let a_left = std::cmp::min(plot_data.start as u32, plot_data.end as u32);
                 let a_right = std::cmp::max(plot_data.start as u32, plot_data.end as u32);
                 let (x_0, a, b) = ((a_right + a_left) as f32 / 2.0, (a_right - a_left) as f32 / 2.0, plot_data.height);
-                let (multi, y_shift, epsilon) = (50, self.y_shift, 0.2);
+                let (multi, y_shift, epsilon) = (50, self.y_shift, 0.2);  // epsilon separates the deprel from the arc
                 
                 chart.draw_series(LineSeries::new(((multi * a_left as i32) as u32..=(multi * a_right as i32) as u32).map(|x| x as f32 / multi as f32)
-                .map(|x| (x, y_shift + (((b*b) - (((b*b) / (a*a))*((x-x_0).powi(2)))).powf(0.5)))), &BLACK)).unwrap();
+                .map(|x| (x, y_shift + (((b*b) - (((b*b) / (a*a))*((x-x_0).powi(2)))).powf(0.5)))), line_style)).unwrap();
 
-                chart.draw_series(LineSeries::new(vec![(plot_data.end, y_shift), (plot_data.end + epsilon, y_shift + epsilon)], &BLACK)).unwrap();
-                chart.draw_series(LineSeries::new(vec![(plot_data.end, y_shift), (plot_data.end - epsilon, y_shift + epsilon)], &BLACK)).unwrap();
-                chart.plotting_area().draw(&text_draw(x_0, y_shift + plot_data.height - epsilon, plot_data.deprel.clone())).unwrap();
+                // the arrowhead is drawn on the dependent (end) by default, or on the head (start) if requested
+                let arrow_x = if self.arrow_to_head { plot_data.start } else { plot_data.end };
+                chart.draw_series(LineSeries::new(vec![(arrow_x, y_shift), (arrow_x + arrow_dx, y_shift + arrow_dy)], line_style)).unwrap();
+                chart.draw_series(LineSeries::new(vec![(arrow_x, y_shift), (arrow_x - arrow_dx, y_shift + arrow_dy)], line_style)).unwrap();
+                chart.plotting_area().draw(&text_draw(x_0, y_shift + plot_data.height - epsilon, self.arc_label(&plot_data), &arc_styles[i])).unwrap();
             }
             
-            chart.plotting_area().draw(&text_draw(plot_data.end, self.y_shift / 2.0, plot_data.pos.clone())).unwrap();
-            chart.plotting_area().draw(&text_draw(plot_data.end, 0.0, plot_data.form.clone())).unwrap();
+            let (form, pos) = self.row_labels(&plot_data)?;
+            if self.show_pos {
+                chart.plotting_area().draw(&text_draw(plot_data.end, self.y_shift / 2.0, row_label(pos), &row_styles[i])).unwrap();
+            }
+            chart.plotting_area().draw(&text_draw(plot_data.end, 0.0, row_label(form), &row_styles[i])).unwrap();
+        }
+
+        // the legend is drawn in pixel coordinates, a colored square and the category name in each row
+        if self.show_legend && !category_colors.is_empty() {
+
+            let legend_area = chart.plotting_area().strip_coord_spec();
+            let (font_size, legend_pad) = (font_style.1, self.scaled(LEGEND_PAD) as i32);
+            let row_height = font_size + font_size / 2;
+            let text_width = category_colors.iter().map(|(category, _)| estimate_text_width(category, font_size) as i32).max().unwrap_or(0);
+            let x0 = legend_area.dim_in_pixel().0 as i32 - text_width - font_size - 3 * legend_pad;
+            let y1 = legend_pad + row_height * category_colors.len() as i32;
+            legend_area.draw(&Rectangle::new([(x0 - legend_pad, 0), (x0 + font_size + text_width + 2 * legend_pad, y1 + legend_pad / 2)], self.foreground.stroke_width(self.scaled(1)))).unwrap();
+
+            let legend_style = TextStyle::from(font_style)
+            .color(&self.foreground)
+            .pos(Pos::new(HPos::Left, VPos::Center));
+            for (i, (category, color)) in category_colors.iter().enumerate() {
+                let y = legend_pad + row_height * i as i32;
+                legend_area.draw(&Rectangle::new([(x0, y), (x0 + font_size, y + font_size)], color.filled())).unwrap();
+                legend_area.draw(&Text::new(category.clone(), (x0 + font_size + legend_pad, y + font_size / 2), legend_style.clone())).unwrap();
+            }
         }
 
         Ok(())
@@ -158,10 +789,9 @@ impl WalkTree for Conll2Plot {
         
         // the root element in a conll is the element that is not the child of any other token,
         // thus the head of the root is itself, that what we check.
-        let mut root_id: Option<f32> = None;
-        for i in 0..(&self.tokens).len() {
+        let mut root: Option<&Token> = None;
+        for token in &self.tokens {
 
-            let token = &self.tokens[i as usize];
             let token_head = token.get_token_head();
             let token_id = token.get_token_id();
 
@@ -169,15 +799,15 @@ impl WalkTree for Conll2Plot {
                 continue;
             }
 
-            match root_id {
-                Some(_root_id) => panic!("not supporting more than one root"),
+            match root {
+                Some(_root) => return Err("not supporting more than one root".into()),
                 None => {
-                    root_id = Some(token_id)
+                    root = Some(token)
                 }
             }
         }
-        assert!(root_id.is_some());
-        let root_element_id = Element::TID(&self.tokens[root_id.unwrap() as usize]);
+        let root = root.ok_or("no root was found, the head of the root should be its own id")?;
+        let root_element_id = Element::TID(root);
         Ok(root_element_id)
 
     }
@@ -185,25 +815,25 @@ impl WalkTree for Conll2Plot {
     fn get_children_ids(&self, element_id: Element) -> Result<Vec<Element>, Box<dyn Error>> {
         
         let root_token_id = <&Token>::try_from(element_id)?.get_token_id();
+        let root_position = self.position(root_token_id)?;
 
-        let mut root_children_ids: Vec<(f32, usize)> = Vec::new();
-        for i in 0..(&self.tokens).len() {
+        let mut root_children_ids: Vec<(&Token, usize)> = Vec::new();
+        for token in &self.tokens {
 
-            let token = &self.tokens[i as usize];
             let token_head = token.get_token_head();
             let token_id = token.get_token_id();
 
             if token_head == root_token_id && token_id != root_token_id {
-                let distance = (root_token_id - token_id).abs() as usize;
-                root_children_ids.push((token_id, distance));
+                let distance = root_position.abs_diff(self.position(token_id)?);
+                root_children_ids.push((token, distance));
             }
 
         }
 
         // sort children by distance (ascending order), they will be handled from closer to farther from the current token
         root_children_ids.sort_by(|x, y| x.1.cmp(&y.1));
-        let children_ids = root_children_ids.iter().map(|(token_id, _)| 
-        Element::TID(&self.tokens[*token_id as usize])).collect::<>();
+        let children_ids = root_children_ids.iter().map(|(token, _)| 
+        Element::TID(token)).collect::<>();
         
         Ok(children_ids)
 
@@ -232,7 +862,7 @@ impl WalkActions for Conll2Plot {
     fn post_walk_update(&self, element_id: Element, data: &mut Accumulator) -> Result<(), Box<dyn Error>> {
         let root_token = <&Token>::try_from(element_id)?;
         let walk_data = <&mut WalkData>::try_from(data)?;
-        let this_plot_data = self.extract(root_token, walk_data);
+        let this_plot_data = self.extract(root_token, walk_data)?;
         walk_data.conll_plot_data.push(this_plot_data);
         Ok(())
     }
@@ -245,15 +875,307 @@ impl WalkActions for Conll2Plot {
 
 impl Conll2Plot {
 
+    // A helper method that returns the x position of a token by its id. Positions are contiguous
+    // even if the ids are gapped, ordered by the ids.
+    fn position(&self, id: f32) -> Result<usize, Box<dyn Error>> {
+        match self.id2position.get(&id.to_bits()) {
+            Some(position) => Ok(*position),
+            None => Err(format!("token id {} was not found in tokens", id).into())
+        }
+    }
+
+    // A helper method that returns the tokens from which the walk starts, the root (whose head is itself)
+    // followed by the unattached tokens (whose head does not match any token id).
+    fn walk_roots(&self) -> Result<Vec<&Token>, Box<dyn Error>> {
+        let roots = self.tokens.iter().filter(|token| token.get_token_head() == token.get_token_id()).collect::<Vec<&Token>>();
+        if roots.len() > 1 {
+            return Err("not supporting more than one root".into());
+        }
+        let unattached = self.tokens.iter().filter(|token| !self.id2position.contains_key(&token.get_token_head().to_bits()));
+        Ok(roots.into_iter().chain(unattached).collect::<Vec<&Token>>())
+    }
+
+    // A helper method that extracts the plotting data through recursion. Returns the plotting data and the height
+    // of the highest arc.
+    fn layout(&self) -> Result<(Vec<ConllPlotData>, f32), Box<dyn Error>> {
+        let walk_args: Vec<[f32; 2]> = vec![[0.0, 0.0]; self.tokens.len()];
+        let plot_data_vec: Vec<ConllPlotData> = Vec::new();
+        let walk_data: WalkData = WalkData { conll_plot_data: plot_data_vec, walk_args };
+        let mut accumulator = Accumulator::WD(walk_data);
+
+        // the walk starts from the root and from every unattached token, as the top of their own subtrees
+        for token in self.walk_roots()? {
+            let element_id = Element::TID(token);
+            self.init_walk(element_id, &mut accumulator)?;
+            self.walk(Some(element_id), &mut accumulator)?;
+            self.post_walk_update(element_id, &mut accumulator)?;
+        }
+
+        // return to walk data from the general enum accumulator
+        let walk_data = <&mut WalkData>::try_from(&mut accumulator)?;
+        let mut arcs_height = walk_data.walk_args.concat().iter().map(|x| *x as usize).max().unwrap() as f32;
+
+        // the compact heights are kept unless the heights of the walk are lower, which is possible with crossing arcs
+        if self.compact_layout {
+            let mut compact = walk_data.conll_plot_data.clone();
+            let compact_height = Conll2Plot::compact(&mut compact);
+            if compact_height <= arcs_height {
+                walk_data.conll_plot_data = compact;
+                arcs_height = compact_height;
+            }
+        }
+
+        // arcs from a virtual root are known to clear all other arcs only after the walk, they are raised above them
+        let virtual_root_height = arcs_height + 1.0;
+        for plot_data in walk_data.conll_plot_data.iter_mut().filter(|plot_data| plot_data.virtual_root) {
+            plot_data.height = virtual_root_height;
+            arcs_height = virtual_root_height;
+        }
+
+        // a flat strip has no arcs above the rows
+        if !self.show_arcs {
+            arcs_height = 0.0;
+        }
+        Ok((walk_data.conll_plot_data.clone(), arcs_height))
+    }
+
+    // A helper method that calculates the dimensions of the figure based on the sentence length and the arcs height.
+    // Returns the dimensions in pixels, the font size, the padding of the x axis on each side, and a warning if the
+    // dimensions were clamped.
+    fn figure_dimensions(&self, arcs_height: f32) -> ((u32, u32), i32, f32, Option<Warning>) {
+        let seq_length = self.tokens.len() as f32;
+        if !self.show_arcs {
+            return self.flat_dimensions();
+        }
+        let built_height = self.y_shift + arcs_height;
+
+        // the figure is kept at least as wide as it is tall, otherwise very short sentences (like a single
+        // token) result in a narrow figure with an oversized font. The extra width is padded evenly on the x axis.
+        let width_units = seq_length.max(built_height);
+        let x_pad = (width_units - seq_length) / 2.0;
+        let total_units = 2*DIM_CONST / (width_units + built_height) as u32;
+        let width = total_units * width_units as u32;
+        let height = total_units * built_height as u32;
+        let fig_dims: (u32, u32) = (width.max(MIN_DIM), height.max(MIN_DIM));
+        let clamped = match fig_dims != (width, height) {
+            true => Some(Warning::DimensionsClamped((width, height), fig_dims)),
+            false => None
+        };
+        let (width, height) = fig_dims;
+
+        // calculate dynamic font size
+        let font_size = (FONT_CONST * (height as f32 / width as f32) * FONT_SIZE * self.scale) as i32;
+        ((self.scaled(width), self.scaled(height)), font_size, x_pad, clamped)
+    }
+
+    // A helper method that calculates the dimensions of a flat strip (without arcs), by a fixed width of every token
+    // and a fixed height of every row on top of the margins. The font size is not reduced with the sentence length.
+    fn flat_dimensions(&self) -> ((u32, u32), i32, f32, Option<Warning>) {
+        let width = FLAT_TOKEN_WIDTH * self.tokens.len() as u32 + 2 * self.margin + Y_LABEL_AREA;
+        let height = FLAT_ROW_HEIGHT * self.y_shift as u32 + 2 * self.margin + X_LABEL_AREA;
+        let fig_dims: (u32, u32) = (width.max(MIN_DIM), height.max(MIN_DIM));
+        let clamped = match fig_dims != (width, height) {
+            true => Some(Warning::DimensionsClamped((width, height), fig_dims)),
+            false => None
+        };
+        let font_size = (FONT_SIZE * self.scale) as i32;
+        ((self.scaled(fig_dims.0), self.scaled(fig_dims.1)), font_size, 0.0, clamped)
+    }
+
+    // A helper method that multiplies a size in pixels by the scale factor of the figure.
+    fn scaled(&self, size: u32) -> u32 {
+        (size as f32 * self.scale).round() as u32
+    }
+
+    // A helper method that draws the plot on the given drawing area, the backend is determined by the caller.
+    fn draw<'b, DB: OverlayBackend<'b>>(&mut self, root_area: DrawingArea<DB, Shift>, plot_data_vec: Vec<ConllPlotData>, x_spec: std::ops::Range<f32>, font_style: (&str, i32)) -> Result<(), Box<dyn Error>>
+    where DB::ErrorType: 'static {
+
+        root_area.fill(&self.background).unwrap();
+        // the rows of a flat strip are centered in the plotting area, one unit for every row
+        let y_spec = match self.show_arcs {
+            true => std::ops::Range{start: 0.0, end: 10.0},
+            false => std::ops::Range{start: -0.5, end: self.y_shift - 0.5}
+        };
+
+        let mut chart = ChartBuilder::on(&root_area)
+        .margin(self.scaled(self.margin))
+        .x_label_area_size(self.scaled(X_LABEL_AREA))
+        .y_label_area_size(self.scaled(Y_LABEL_AREA))
+        .build_cartesian_2d(x_spec, y_spec).unwrap();
+
+        chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .disable_y_mesh()
+        .disable_x_axis()
+        .disable_y_axis()
+        .draw()
+        .unwrap();
+
+        // warn about forms and pos tags that are estimated to overlap their neighbors (unless truncated)
+        if !self.truncate_labels {
+            let token_width = Conll2Plot::unit_pixels(&chart).0;
+            for plot_data in plot_data_vec.iter() {
+                let (form, pos) = self.row_labels(plot_data)?;
+                if overflows(&form, token_width, font_style.1) {
+                    self.warnings.push(Warning::LabelOverflow(form));
+                }
+                if self.show_pos && overflows(&pos, token_width, font_style.1) {
+                    self.warnings.push(Warning::LabelOverflow(pos));
+                }
+            }
+        }
+
+        self.plot(&mut chart, plot_data_vec, font_style)?;
+        if let Some(overlay) = &self.overlay {
+            overlay(DB::overlay_chart(&mut chart))?;
+        }
+        root_area.present()?;
+        Ok(())
+    }
+
+    // A helper method that returns the label written above an arc, the deprel optionally followed by the arc length.
+    fn arc_label(&self, plot_data: &ConllPlotData) -> String {
+        let deprel = self.abbreviated(&plot_data.deprel);
+        match self.show_arc_length && !plot_data.virtual_root {
+            true => format!("{} ({})", deprel, (plot_data.start - plot_data.end).abs().round() as usize),
+            false => deprel
+        }
+    }
+
+    // A helper method that returns the abbreviation of a pos tag or a deprel, or the tag itself if it has none.
+    fn abbreviated(&self, tag: &str) -> String {
+        self.abbreviations.get(tag).cloned().unwrap_or_else(|| tag.to_string())
+    }
+
+    // A helper method that returns the line width of every arc in pixels, proportional to the weights of the tokens
+    // if a weight key is given. Tokens without a valid weight get the default width of a single (scaled) pixel.
+    fn arc_widths(&self, plot_data_vec: &[ConllPlotData]) -> Result<Vec<u32>, Box<dyn Error>> {
+        let weights = plot_data_vec.iter().map(|plot_data| {
+            let token = self.token_at(plot_data.end as usize)?;
+            Ok(match (&self.weight_key, token) {
+                (Some(weight_key), Some(token)) => misc_value(token, weight_key).and_then(|value| value.parse::<f32>().ok())
+                .filter(|weight| weight.is_finite() && *weight >= 0.0),
+                _ => None
+            })
+        }).collect::<Result<Vec<Option<f32>>, Box<dyn Error>>>()?;
+
+        let max_weight = weights.iter().flatten().fold(0.0, |x: f32, y| x.max(*y));
+        Ok(weights.iter().map(|weight| match weight {
+            Some(weight) if max_weight > 0.0 => self.scaled((MAX_ARC_WIDTH * weight / max_weight).round().max(1.0) as u32),
+            _ => self.scaled(1)
+        }).collect::<Vec<u32>>())
+    }
+
+    // A helper method that returns the category of a token by which it is colored, if requested.
+    fn category_of<'a>(&self, deprel: &'a str, pos: &'a str) -> Option<&'a str> {
+        match self.color_by {
+            Some(ColorBy::Deprel) => Some(deprel),
+            Some(ColorBy::Pos) => Some(pos),
+            None => None
+        }
+    }
+
+    // A helper method that returns the color of the user predicate (if given) for the token at the given position.
+    fn token_color_at(&self, position: usize) -> Result<Option<RGBColor>, Box<dyn Error>> {
+        match (&self.token_color, self.token_at(position)?) {
+            (Some(token_color), Some(token)) => Ok(token_color(token).map(|(r, g, b)| RGBColor(r, g, b))),
+            _ => Ok(None)
+        }
+    }
+
+    // A helper method that returns the texts of the form row and the pos row of a token, formatted by the user callback if given
+    // (otherwise the pos tag is abbreviated).
+    fn row_labels(&self, plot_data: &ConllPlotData) -> Result<(String, String), Box<dyn Error>> {
+        match (&self.token_label_format, self.token_at(plot_data.end as usize)?) {
+            (Some(token_label_format), Some(token)) => Ok(token_label_format(token)),
+            _ => Ok((plot_data.form.clone(), self.abbreviated(&plot_data.pos)))
+        }
+    }
+
+    // A helper method that returns the token at the given position, if exists.
+    fn token_at(&self, position: usize) -> Result<Option<&Token>, Box<dyn Error>> {
+        for token in &self.tokens {
+            if self.position(token.get_token_id())? == position {
+                return Ok(Some(token));
+            }
+        }
+        Ok(None)
+    }
+
+    // A helper method that assigns a distinct color to every category, by order of appearance in the tokens.
+    fn category_colors(&self) -> Vec<(String, RGBColor)> {
+        let mut category_colors: Vec<(String, RGBColor)> = Vec::new();
+        for token in &self.tokens {
+            let (deprel, pos) = (token.get_token_deprel(), token.get_token_pos());
+            if let Some(category) = self.category_of(&deprel, &pos) {
+                if !category_colors.iter().any(|(x, _)| x == category) {
+                    let (r, g, b) = Palette99::pick(category_colors.len()).rgb();
+                    category_colors.push((category.to_string(), RGBColor(r, g, b)));
+                }
+            }
+        }
+        category_colors
+    }
+
+    // A helper method that reassigns the heights of the arcs for the compact layout, and returns the height of the
+    // highest arc. The arcs are placed from the shortest, so the arcs nested in an arc (including arcs that share
+    // an endpoint on its inner side) are placed before it. Every arc is placed above them, at the lowest height that
+    // is not taken by a placed arc that crosses it.
+    fn compact(plot_data_vec: &mut [ConllPlotData]) -> f32 {
+        let span = |plot_data: &ConllPlotData| (plot_data.start.min(plot_data.end), plot_data.start.max(plot_data.end));
+        let mut arcs = (0..plot_data_vec.len())
+        .filter(|i| plot_data_vec[*i].height >= 0.0 && !plot_data_vec[*i].virtual_root)
+        .collect::<Vec<usize>>();
+        arcs.sort_by(|i, j| {
+            let ((i_left, i_right), (j_left, j_right)) = (span(&plot_data_vec[*i]), span(&plot_data_vec[*j]));
+            (i_right - i_left).total_cmp(&(j_right - j_left))
+        });
+
+        let mut placed: Vec<((f32, f32), f32)> = Vec::new();
+        for i in arcs {
+            let (left, right) = span(&plot_data_vec[i]);
+            let nested = placed.iter().filter(|((x, y), _)| left <= *x && *y <= right).map(|(_, height)| *height);
+            let crossing = placed.iter().filter(|((x, y), _)| (*x < left && left < *y && *y < right) || (left < *x && *x < right && right < *y))
+            .map(|(_, height)| *height).collect::<Vec<f32>>();
+
+            let mut height = 1.0 + nested.fold(0.0, f32::max);
+            while crossing.contains(&height) {
+                height += 1.0;
+            }
+            plot_data_vec[i].height = height;
+            placed.push(((left, right), height));
+        }
+        placed.iter().map(|(_, height)| *height).fold(0.0, f32::max)
+    }
+
+    // A helper method that returns the size in pixels of a single unit on the x and y axes of the chart.
+    // One unit on the x axis is the width of a single token.
+    fn unit_pixels<'a, DB, CT>(chart: &ChartContext<'a, DB, CT>) -> (f32, f32)
+    where DB: DrawingBackend + 'a, CT: CoordTranslate<From = (f32, f32)> {
+        let coord_spec = chart.as_coord_spec();
+        let (x0, y0) = coord_spec.translate(&(0.0, 0.0));
+        let (x1, y1) = coord_spec.translate(&(1.0, 1.0));
+        ((x1 - x0) as f32, (y0 - y1) as f32)
+    }
+
     // most of the calculation regarding the locations is done in this helper method, since
     // it is not similar to constituency was kept exclusive to this structure.
     // The main idea of calculation is that a vector of counts is updated dynamically, and stores the
     // height that the next token's arrow should be drawn on, based on the entry and finish position on x
     // axis. This helpes drawing arrows on minimal height that's needed to not have arrow clashes.
-    fn extract(&self, token: &Token, walk_data: &mut WalkData) -> ConllPlotData {
-
-        let token_head = token.get_token_head();
-        let token_id = token.get_token_id();
+    fn extract(&self, token: &Token, walk_data: &mut WalkData) -> Result<ConllPlotData, Box<dyn Error>> {
+
+        // from here on the calculation is made on positions rather than ids, which may be gapped.
+        // an unattached token is placed as its own head, so it is handled as the root case (no arc),
+        // a token attached to a virtual root as well, its arc is raised above all others in build
+        let token_id = self.position(token.get_token_id())? as f32;
+        let virtual_root = token.get_token_head() == VIRTUAL_ROOT_HEAD && !self.id2position.contains_key(&VIRTUAL_ROOT_HEAD.to_bits());
+        let token_head = match self.id2position.get(&token.get_token_head().to_bits()) {
+            Some(position) => *position as f32,
+            None => token_id
+        };
 
         let mut update = || {
 
@@ -295,13 +1217,449 @@ impl Conll2Plot {
             start: token_head,
             end: token_id,
             deprel: token.get_token_deprel(),
-            form: token.get_token_form(),
-            pos: token.get_token_pos(),
-            height: height
+            form: self.row_fields.0.of(token),
+            pos: self.row_fields.1.of(token),
+            height,
+            virtual_root
         };
 
-        return plot_args;
+        Ok(plot_args)
+
+    }
+
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Conll2Plot, ColorBy, ConllPlotData, TokenField, AGREEMENT_COLOR, DISAGREEMENT_COLOR};
+    use plotters::style::RGBColor;
+    use plotters::prelude::{Circle, RED};
+    use crate::{String2StructureBuilder, String2Conll, Structure2PlotBuilder, OverlayChart};
 
+    fn conll2plot_template(example: Vec<&str>, save_to: &str) -> Conll2Plot {
+        let mut dependency = example.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let conll = string2conll.get_structure();
+
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(conll);
+        conll2plot.build(save_to).unwrap();
+        conll2plot
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn gapped_ids() {
+        let conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	3	nsubj	_	_",
+            "3	watch	watch	VERB	_	_	3	ROOT	_	_",
+            "4	games	game	NOUN	_	_	3	dobj	_	_"
+        ], "Output/dependency_gapped.png");
+
+        assert_eq!(conll2plot.position(3.0).unwrap(), 2);
+        assert_eq!(conll2plot.position(4.0).unwrap(), 3);
+        assert!(conll2plot.position(2.0).is_err());
+    }
+
+    #[test]
+    fn arc_length() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	3	nsubj	_	_",
+            "3	watch	watch	VERB	_	_	3	ROOT	_	_"
+        ], "Output/dependency_arc_length.png");
+
+        let plot_data = ConllPlotData { start: 2.0, end: 1.0, deprel: "nsubj".to_string(), pos: "NOUN".to_string(), form: "people".to_string(), height: 1.0, virtual_root: false };
+        assert_eq!(conll2plot.arc_label(&plot_data), "nsubj");
+        conll2plot.set_show_arc_length(true);
+        assert_eq!(conll2plot.arc_label(&plot_data), "nsubj (1)");
+        conll2plot.build("Output/dependency_arc_length.png").unwrap();
+    }
+
+    #[test]
+    fn single_token() {
+        let save_to = "Output/dependency_single.png";
+        let conll2plot = conll2plot_template(vec![
+            "0	Hello	hello	INTJ	_	_	0	ROOT	_	_"
+        ], save_to);
+        assert!(conll2plot.get_warnings().is_empty());
+
+        // width and height are stored in the IHDR chunk of the png
+        let png = std::fs::read(save_to).unwrap();
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        assert!(width >= height, "degenerate single token figure of {}x{}", width, height);
+    }
+
+    #[test]
+    fn compute_dimensions() {
+        let save_to = "Output/dependency_dimensions.png";
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ], save_to);
+        let png = std::fs::read(save_to).unwrap();
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        assert_eq!(conll2plot.compute_dimensions().unwrap(), (width, height));
+
+        conll2plot.set_scale(2.0).unwrap();
+        assert_eq!(conll2plot.compute_dimensions().unwrap(), (2 * width, 2 * height));
+        assert!(conll2plot.set_scale(0.0).is_err() && conll2plot.set_scale(f32::NAN).is_err());
+        assert_eq!(conll2plot.compute_dimensions().unwrap(), (2 * width, 2 * height));
+    }
+
+    #[test]
+    fn abbreviations() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	John	john	PROPN	_	_	1	nsubj	_	_",
+            "1	left	leave	VERB	_	_	1	root	_	_",
+            "2	yesterday	yesterday	NOUN	_	_	1	obl:tmod	_	_"
+        ], "Output/dependency_abbreviations.png");
+        let abbreviations = [("PROPN", "PN"), ("obl:tmod", "tmod")];
+        conll2plot.set_abbreviations(abbreviations.iter().map(|(x, y)| (x.to_string(), y.to_string())).collect());
+        conll2plot.set_show_arc_length(true);
+
+        let plot_data = ConllPlotData { start: 1.0, end: 2.0, deprel: "obl:tmod".to_string(), pos: "NOUN".to_string(), form: "yesterday".to_string(), height: 1.0, virtual_root: false };
+        assert_eq!(conll2plot.arc_label(&plot_data), "tmod (1)");
+        assert_eq!(conll2plot.row_labels(&plot_data).unwrap(), ("yesterday".to_string(), "NOUN".to_string()));
+        let plot_data = ConllPlotData { start: 1.0, end: 0.0, deprel: "nsubj".to_string(), pos: "PROPN".to_string(), form: "John".to_string(), height: 1.0, virtual_root: false };
+        assert_eq!(conll2plot.row_labels(&plot_data).unwrap(), ("John".to_string(), "PN".to_string()));
+
+        // the tokens keep their tags
+        conll2plot.build("Output/dependency_abbreviations.png").unwrap();
+        assert_eq!(conll2plot.get_tokens()[0].get_token_pos(), "PROPN");
+    }
+
+    #[test]
+    fn png_text() {
+        let mut dependency = std::fs::read_to_string("Input/conll_ud.txt").unwrap().lines().map(|x| x.to_string()).collect::<Vec<String>>();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.set_one_based(true);
+        string2conll.build(&mut dependency).unwrap();
+        let sentence = string2conll.get_sentence();
+
+        let save_to = "Output/dependency_png_text.png";
+        let mut conll2plot = Conll2Plot::from(sentence.clone());
+        conll2plot.add_png_text("Title", &sentence.get_sent_id().unwrap()).unwrap();
+        conll2plot.add_png_text("Description", &sentence.get_text().unwrap()).unwrap();
+        conll2plot.build(save_to).unwrap();
+
+        // the png is still valid, and holds the metadata
+        let png = std::fs::read(save_to).unwrap();
+        assert!(image::load_from_memory(&png).is_ok());
+        let text_chunk = |keyword: &str, text: &str| [b"iTXt", keyword.as_bytes(), &[0; 5], text.as_bytes()].concat();
+        let description = text_chunk("Description", "What if Google Morphed Into GoogleOS?");
+        assert!(png.windows(description.len()).any(|x| x == description));
+
+        // an invalid keyword is rejected before it is added, the next builds still succeed
+        assert!(conll2plot.add_png_text("", "no keyword").is_err());
+        assert!(conll2plot.add_png_text("Tab\tbed", "no keyword").is_err());
+        conll2plot.build(save_to).unwrap();
+        assert_eq!(std::fs::read(save_to).unwrap(), png);
+    }
+
+    #[test]
+    fn flat() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ], "Output/dependency_flat.png");
+
+        // the strip is as wide as its tokens and as tall as its two rows, regardless of the arcs
+        conll2plot.set_show_arcs(false);
+        assert_eq!(conll2plot.compute_dimensions().unwrap(), (5 * 80 + 2 * 15 + 50, 2 * 30 + 2 * 15 + 10));
+        conll2plot.build("Output/dependency_flat.png").unwrap();
+        assert!(conll2plot.get_warnings().is_empty());
+
+        conll2plot.set_show_pos(false);
+        assert_eq!(conll2plot.compute_dimensions().unwrap(), (5 * 80 + 2 * 15 + 50, 100));
+    }
+
+    #[test]
+    fn gold() {
+        let gold = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ], "Output/dependency_gold.png").tokens;
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	obj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ], "Output/dependency_gold.png");
+
+        // a gold parse of another sentence is rejected
+        assert!(conll2plot.set_gold(Some(gold[..2].to_vec())).is_err());
+        assert!(conll2plot.gold.is_none());
+
+        // the agreeing arc of "The" is green and the disagreeing arc of "people" is red
+        conll2plot.set_gold(Some(gold)).unwrap();
+        conll2plot.build("Output/dependency_gold.png").unwrap();
+        let image = image::open("Output/dependency_gold.png").unwrap().to_rgb8();
+        for color in [AGREEMENT_COLOR, DISAGREEMENT_COLOR] {
+            assert!(image.pixels().any(|pixel| pixel.0 == [color.0, color.1, color.2]));
+        }
+    }
+
+    #[test]
+    fn compact_layout() {
+        // a chain of arcs nested in the arc of "a", which the walk reaches before them, crossed by the arc of "e"
+        let mut conll2plot = conll2plot_template(vec![
+            "0	a	a	X	_	_	4	dep	_	_",
+            "1	b	b	X	_	_	0	dep	_	_",
+            "2	c	c	X	_	_	0	dep	_	_",
+            "3	d	d	X	_	_	3	ROOT	_	_",
+            "4	e	e	X	_	_	3	dep	_	_",
+            "5	f	f	X	_	_	3	dep	_	_"
+        ], "Output/dependency_nested.png");
+        assert_eq!(conll2plot.layout().unwrap().1, 5.0);
+
+        conll2plot.set_compact_layout(true);
+        let (plot_data_vec, arcs_height) = conll2plot.layout().unwrap();
+        assert_eq!(arcs_height, 3.0);
+        let height_of = |form: &str| plot_data_vec.iter().find(|x| x.form == form).unwrap().height;
+        assert!(height_of("b") < height_of("c") && height_of("c") < height_of("a") && height_of("e") < height_of("a"));
+        conll2plot.build("Output/dependency_compact.png").unwrap();
+
+        // a projective parse is kept
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ], "Output/dependency_projective.png");
+        let heights = conll2plot.layout().unwrap().0.iter().map(|x| x.height).collect::<Vec<f32>>();
+        conll2plot.set_compact_layout(true);
+        assert_eq!(conll2plot.layout().unwrap().0.iter().map(|x| x.height).collect::<Vec<f32>>(), heights);
+    }
+
+    #[test]
+    fn row_fields() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	people	person	NOUN	NNS	Number=Plur	1	nsubj	_	_",
+            "1	watch	watch	VERB	VBP	_	1	ROOT	_	_"
+        ], "Output/dependency_rows.png");
+        let rows = |conll2plot: &Conll2Plot| conll2plot.layout().unwrap().0.iter().map(|x| (x.form.clone(), x.pos.clone())).collect::<Vec<(String, String)>>();
+        assert!(rows(&conll2plot).contains(&("people".to_string(), "NOUN".to_string())));
+
+        conll2plot.set_row_fields(TokenField::Lemma, TokenField::Xpos);
+        assert!(rows(&conll2plot).contains(&("person".to_string(), "NNS".to_string())));
+        conll2plot.set_row_fields(TokenField::Feats, TokenField::Deprel);
+        assert!(rows(&conll2plot).contains(&("Number=Plur".to_string(), "nsubj".to_string())));
+
+        // the colors are still by the pos tags
+        conll2plot.set_color_by(Some(ColorBy::Pos));
+        assert_eq!(conll2plot.category_colors().iter().map(|(x, _)| x.as_str()).collect::<Vec<&str>>(), vec!["NOUN", "VERB"]);
+        conll2plot.build("Output/dependency_rows.png").unwrap();
+    }
+
+    #[test]
+    fn weights() {
+        let mut dependency = [
+            "0	The	the	DET	_	_	1	det	_	Confidence=1.0",
+            "1	people	people	NOUN	_	_	2	nsubj	_	SpaceAfter=No|Confidence=0.5",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	Confidence=high",
+            "4	game	game	NOUN	_	_	2	dobj	_	Confidence=0.05"
+        ].map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+
+        let plot_data_vec = conll2plot.layout().unwrap().0;
+        let by_position = |widths: Vec<u32>| {
+            let mut widths = plot_data_vec.iter().map(|x| x.end as usize).zip(widths).collect::<Vec<(usize, u32)>>();
+            widths.sort();
+            widths.into_iter().map(|(_, width)| width).collect::<Vec<u32>>()
+        };
+        assert_eq!(by_position(conll2plot.arc_widths(&plot_data_vec).unwrap()), vec![1; 5]);
+
+        // invalid and missing weights are drawn in the default width, the smallest weights are at least as wide
+        conll2plot.set_weight_key(Some("Confidence"));
+        assert_eq!(by_position(conll2plot.arc_widths(&plot_data_vec).unwrap()), vec![5, 3, 1, 1, 1]);
+        conll2plot.set_scale(2.0).unwrap();
+        assert_eq!(by_position(conll2plot.arc_widths(&plot_data_vec).unwrap()), vec![10, 6, 2, 2, 2]);
+        conll2plot.build("Output/dependency_weights.png").unwrap();
+    }
+
+    #[test]
+    fn color_legend() {
+        let mut dependency = [
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ].map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+        conll2plot.set_color_by(Some(ColorBy::Deprel));
+        let categories = conll2plot.category_colors().into_iter().map(|(x, _)| x).collect::<Vec<String>>();
+        assert_eq!(categories, vec!["det", "nsubj", "ROOT", "dobj"]);
+
+        conll2plot.set_color_by(Some(ColorBy::Pos));
+        assert_eq!(conll2plot.category_colors().len(), 3);
+        conll2plot.set_show_legend(true);
+        conll2plot.build("Output/dependency_legend.png").unwrap();
+    }
+
+    #[test]
+    fn token_color() {
+        let mut dependency = [
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ].map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+        assert_eq!(conll2plot.token_color_at(0).unwrap(), None);
+        conll2plot.set_token_color(|token| match token.get_token_lemma().as_str() {
+            "the" => Some((255, 0, 0)),
+            _ => None
+        });
+        assert_eq!(conll2plot.token_color_at(3).unwrap(), Some(RGBColor(255, 0, 0)));
+        assert_eq!(conll2plot.token_color_at(4).unwrap(), None);
+        conll2plot.build("Output/dependency_token_color.png").unwrap();
+    }
+
+    #[test]
+    fn token_label_format() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	person	NOUN	_	_	1	ROOT	_	_"
+        ], "Output/dependency_token_label_format.png");
+
+        let plot_data = ConllPlotData { start: 1.0, end: 1.0, deprel: "ROOT".to_string(), pos: "NOUN".to_string(), form: "people".to_string(), height: -1.0, virtual_root: false };
+        assert_eq!(conll2plot.row_labels(&plot_data).unwrap(), ("people".to_string(), "NOUN".to_string()));
+        conll2plot.set_token_label_format(|token| (token.get_token_lemma(), token.get_token_deprel()));
+        assert_eq!(conll2plot.row_labels(&plot_data).unwrap(), ("person".to_string(), "ROOT".to_string()));
+        conll2plot.build("Output/dependency_token_label_format.png").unwrap();
+    }
+
+    #[test]
+    fn clone() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	person	NOUN	_	_	1	ROOT	_	_"
+        ], "Output/dependency_clone.png");
+        conll2plot.set_token_label_format(|token| (token.get_token_lemma(), token.get_token_deprel()));
+
+        let mut variant = conll2plot.clone();
+        variant.set_show_pos(false);
+        let plot_data = ConllPlotData { start: 1.0, end: 1.0, deprel: "ROOT".to_string(), pos: "NOUN".to_string(), form: "people".to_string(), height: -1.0, virtual_root: false };
+        assert_eq!(variant.row_labels(&plot_data).unwrap(), ("person".to_string(), "ROOT".to_string()));
+        assert!(conll2plot.show_pos && !variant.show_pos);
+        variant.build("Output/dependency_clone_variant.png").unwrap();
+
+        // a configured plotter can be built on another thread
+        std::thread::spawn(move || conll2plot.build("Output/dependency_clone.png").unwrap()).join().unwrap();
+    }
+
+    #[test]
+    fn tokens() {
+        let conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	person	NOUN	_	_	1	ROOT	_	_"
+        ], "Output/dependency_tokens.png");
+        let lemmas = conll2plot.get_tokens().iter().map(|x| x.get_token_lemma()).collect::<Vec<String>>();
+        assert_eq!(lemmas, vec!["the", "person"]);
+    }
+
+    #[test]
+    fn overlay() {
+        let mut conll2plot = conll2plot_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	person	NOUN	_	_	1	ROOT	_	_"
+        ], "Output/dependency_overlay.svg");
+
+        conll2plot.set_overlay(|chart| match chart {
+            OverlayChart::Svg(chart) => {
+                chart.draw_series(std::iter::once(Circle::new((1.0, 5.0), 10, RED)))?;
+                Ok(())
+            },
+            OverlayChart::Bitmap(_) => Err("an svg is drawn by the svg backend".into())
+        });
+        conll2plot.build("Output/dependency_overlay.svg").unwrap();
+        assert!(std::fs::read_to_string("Output/dependency_overlay.svg").unwrap().contains("<circle"));
+    }
+
+    #[test]
+    fn unattached_heads() {
+        // a virtual root 0 that is not part of the tokens, and an unattached token marked with "_"
+        let conll2plot = conll2plot_template(vec![
+            "1	The	the	DET	_	_	2	det	_	_",
+            "2	people	people	NOUN	_	_	3	nsubj	_	_",
+            "3	watch	watch	VERB	_	_	0	root	_	_",
+            "4	!	!	PUNCT	_	_	_	_	_	_"
+        ], "Output/dependency_unattached.png");
+
+        let roots = conll2plot.walk_roots().unwrap().iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(roots, vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn virtual_root() {
+        // the UD convention, ids start at 1 and the root is attached to a virtual root 0
+        let example = vec![
+            "1	The	the	DET	_	_	2	det	_	_",
+            "2	people	people	NOUN	_	_	3	nsubj	_	_",
+            "3	watch	watch	VERB	_	_	0	root	_	_",
+            "4	!	!	PUNCT	_	_	_	_	_	_"
+        ];
+        // the deprel is written only above an arc, every text of the svg is in its own line
+        let arc_labels = |example: Vec<&str>, save_to: &str| {
+            let mut conll2plot = conll2plot_template(example, "Output/dependency_virtual_root.png");
+            conll2plot.build(save_to).unwrap();
+            let svg = std::fs::read_to_string(save_to).unwrap();
+            ["root", "ROOT", "_"].into_iter().filter(|deprel| svg.lines().any(|line| line.trim() == *deprel)).collect::<Vec<&str>>()
+        };
+        assert_eq!(arc_labels(example, "Output/dependency_virtual_root.svg"), vec!["root"]);
+
+        // a token 0 is a real root, not a virtual one
+        assert!(arc_labels(vec!["0	Hello	hello	INTJ	_	_	0	ROOT	_	_"], "Output/dependency_token_root.svg").is_empty());
+    }
+
+    #[test]
+    fn one_based() {
+        // a UD sentence, from the fixture to the plot
+        let mut dependency = std::fs::read_to_string("Input/conll_ud.txt").unwrap().lines().map(|x| x.to_string()).collect::<Vec<String>>();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.set_one_based(true);
+        string2conll.build(&mut dependency).unwrap();
+
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+        conll2plot.build("Output/dependency_one_based.png").unwrap();
+        let roots = conll2plot.walk_roots().unwrap().iter().map(|x| x.get_token_form()).collect::<Vec<String>>();
+        assert_eq!(roots, vec!["What"]);
+        let heads = conll2plot.get_tokens().iter().map(|x| x.get_token_head()).collect::<Vec<f32>>();
+        assert_eq!(heads, vec![0.0, 3.0, 3.0, 0.0, 5.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn multiple_roots() {
+        let mut dependency = [
+            "0	Hello	hello	INTJ	_	_	0	ROOT	_	_",
+            "1	world	world	NOUN	_	_	1	ROOT	_	_"
+        ].map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(string2conll.get_structure());
+        let error = conll2plot.build("Output/dependency_roots.png").unwrap_err();
+        assert_eq!(error.to_string(), "not supporting more than one root");
+    }
+}
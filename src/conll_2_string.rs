@@ -3,51 +3,157 @@
 // Under MIT license
 //
 
-use super::string_2_conll::Token;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use super::string_2_conll::{Token, Sentence, EMPTY_FIELD, UNATTACHED_HEAD};
 use super::config::configure_structures::Saver;
 use super::generic_enums::{Accumulator, Element};
 use super::generic_traits::generic_traits::{WalkActions, WalkTree, Structure2PlotBuilder};
 
+const SENTENCE_SEPARATOR: &str = "\n\n";
+const ALIGNED_COLUMN_GAP: usize = 2;
+
 /// A Conll2String struct, mainly holds the vec tokens object. This type will implement Structure2PlotBuilder,
 /// WalkTree and WalkActions, with an ultimate goal of saving a dependency to file.
 pub struct Conll2String {
     tokens: Vec<Token>,
-    output: Option<Vec<String>>
+    comments: Vec<String>,
+    output: Option<Vec<String>>,
+    aligned: bool
 }
 
 impl Conll2String {
 
+    ///
+    /// A set method to determine whether the columns are padded with spaces to aligned widths, for human review,
+    /// rather than separated by tabs (default false). Every column is as wide as its longest field, plus a gap of
+    /// two spaces. An aligned conll is not meant to be read back, tabs are kept by default for round trips.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2String;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+    ///     "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut dependency).unwrap();
+    /// 
+    /// let mut conll2string: Conll2String = Structure2PlotBuilder::new(string2conll.get_structure());
+    /// conll2string.set_aligned(true);
+    /// conll2string.build("Output/dependency_aligned.txt").unwrap();
+    /// assert_eq!(conll2string.get_conll(), vec![
+    ///     "0  The     the     DET   _  _  1  det   _  _",
+    ///     "1  people  people  NOUN  _  _  1  ROOT  _  _"
+    /// ]);
+    /// ```
+    ///
+    pub fn set_aligned(&mut self, aligned: bool) {
+        self.aligned = aligned;
+    }
+
+    ///
     /// A method to retrieve the dependency conll after building it from the Vec-token-.
     /// Can be called only after build() has been called. See example on lib.rs.
+    ///
     pub fn get_conll(self) -> Vec<String> {
         assert!(self.output.is_some(), "build most be evoked before retrival of conll");
         let conll = self.output.unwrap().clone();
         conll
     }
-}
 
-impl Structure2PlotBuilder<Vec<Token>> for Conll2String {
-    fn new(structure: Vec<Token>) -> Self {
-        Self {
-            tokens: structure,
-            output: None
-        }
+    ///
+    /// A get method to retrieve the tokens that the conll is made from, available right after initialization.
+    ///
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens
     }
 
-    fn build(&mut self, save_to: &str) -> Result<(), Box<dyn std::error::Error>> {
-        
+    ///
+    /// A method to build the dependency conll from the Vec-token- and write it to any writer (an in-memory
+    /// buffer, an open file, etc.) rather than to a file path. The conll can be retrieved afterwards as in build().
+    ///
+    pub fn build_to_writer<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+
         let mut accumulator = Accumulator::C2S(Vec::<String>::new());
         self.walk(None, &mut accumulator)?;
 
         // move from accumulator vec string to vec string
-        let prediction = <&mut Vec<String>>::try_from(&mut accumulator).unwrap();
+        let prediction = <&mut Vec<String>>::try_from(&mut accumulator)?;
 
-        // save to file and set output
-        vec![prediction.clone()].save_output(save_to)?;
+        // write and set output
+        vec![prediction.clone()].write_output(writer)?;
         self.output = Some(prediction.clone());
 
         Ok(())
+    }
+
+    ///
+    /// A method to build the dependency conll and append it to the file in save_to (created if missing), rather
+    /// than overwriting it. Sentences are separated by a blank line, so a whole corpus can be reconstructed into
+    /// one conll file, sentence by sentence. The conll can be retrieved afterwards as in build().
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::Conll2String;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let save_to = "Output/dependency_appended.txt";
+    /// let _ = std::fs::remove_file(save_to);
+    /// for example in [["0\tHello\thello\tINTJ\t_\t_\t0\tROOT\t_\t_"], ["0\tBye\tbye\tINTJ\t_\t_\t0\tROOT\t_\t_"]] {
+    ///     let mut dependency = example.map(|x| x.to_string()).to_vec();
+    ///     let mut string2conll: String2Conll = String2StructureBuilder::new();
+    ///     string2conll.build(&mut dependency).unwrap();
+    /// 
+    ///     let mut conll2string: Conll2String = Structure2PlotBuilder::new(string2conll.get_structure());
+    ///     conll2string.build_append(save_to).unwrap();
+    /// }
+    /// assert_eq!(std::fs::read_to_string(save_to).unwrap().split("\n\n").count(), 2);
+    /// ```
+    /// 
+    pub fn build_append(&mut self, save_to: &str) -> Result<(), Box<dyn Error>> {
+        let mut out_file = OpenOptions::new().create(true).append(true).open(save_to)?;
+
+        // a single conll is written without a trailing new line, a blank line separates it from the previous one
+        if out_file.metadata()?.len() > 0 {
+            out_file.write_all(SENTENCE_SEPARATOR.as_bytes())?;
+        }
+        self.build_to_writer(&mut out_file)
+    }
+}
 
+// A Conll2String made from a Sentence reconstructs the metadata comments before the tokens
+impl From<Sentence> for Conll2String {
+    fn from(sentence: Sentence) -> Self {
+        let comments = sentence.get_comments();
+        let mut conll2string: Conll2String = Structure2PlotBuilder::new(Vec::<Token>::from(sentence));
+        conll2string.comments = comments;
+        conll2string
+    }
+}
+
+impl Structure2PlotBuilder<Vec<Token>> for Conll2String {
+    fn new(structure: Vec<Token>) -> Self {
+        Self {
+            tokens: structure,
+            comments: Vec::new(),
+            output: None,
+            aligned: false
+        }
+    }
+
+    fn build(&mut self, save_to: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out_file = File::create(save_to)?;
+        self.build_to_writer(&mut out_file)
     }
 }
 
@@ -72,19 +178,37 @@ impl WalkActions for Conll2String {
     fn init_walk(&self, _element_id: Element, data: &mut Accumulator) -> Result<(), Box<dyn std::error::Error>> {
         
         let data_vec = <&mut Vec<String>>::try_from(data)?;
-        for token in &self.tokens {
-            let token_string = [
-                token.get_token_id().to_string(),
-                token.get_token_form(),
-                token.get_token_lemma(),
-                token.get_token_pos(),
-                token.get_token_xpos(),
-                token.get_token_feats(),
-                token.get_token_head().to_string(),
-                token.get_token_deprel(),
-                token.get_token_deps(),
-                token.get_token_misc()
-            ].join("\t");
+        data_vec.extend(self.comments.iter().cloned());
+        let rows = self.tokens.iter().map(|token| [
+            token.get_token_id().to_string(),
+            token.get_token_form(),
+            token.get_token_lemma(),
+            token.get_token_pos(),
+            token.get_token_xpos(),
+            token.get_token_feats(),
+            // an unattached token is written back with the empty head it was read from
+            match token.get_token_head() {
+                UNATTACHED_HEAD => EMPTY_FIELD.to_string(),
+                head => head.to_string()
+            },
+            token.get_token_deprel(),
+            token.get_token_deps(),
+            token.get_token_misc()
+        ]).collect::<Vec<[String; 10]>>();
+
+        if !self.aligned {
+            data_vec.extend(rows.iter().map(|row| row.join("\t")));
+            return Ok(())
+        }
+
+        // every column is padded to its widest field, the last column is not padded to avoid trailing spaces
+        let widths = (0..10).map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or(0)).collect::<Vec<usize>>();
+        for row in rows.iter() {
+            let (last, fields) = row.split_last().unwrap();
+            let mut token_string = fields.iter().zip(widths.iter())
+            .map(|(field, width)| format!("{:<1$}", field, width + ALIGNED_COLUMN_GAP))
+            .collect::<String>();
+            token_string.push_str(last);
             data_vec.push(token_string);
         }
         Ok(())
@@ -138,6 +262,90 @@ mod tests {
         assert_eq!(example, prediction, "\n failed, original example: \n {:?} \n != \n prediction: \n {:?}", example, prediction);
     } 
 
+    #[test]
+    fn unattached() {
+
+        let example = [
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	1	ROOT	_	_",
+            "2	!	!	PUNCT	_	_	_	_	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let prediction = inverse_check(example.clone(), String::from("Output/dependency_inverse_unattached.txt"));
+        assert_eq!(example, prediction);
+    }
+
+
+    #[test]
+    fn conll_sentence() {
+
+        let example = [
+            "# sent_id = 1",
+            "# text = The people",
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	1	ROOT	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut dependency = example.clone();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let sentence = string2conll.get_sentence();
+
+        let mut conll2string = Conll2String::from(sentence);
+        assert_eq!(conll2string.get_tokens().iter().map(|x| x.get_token_form()).collect::<Vec<String>>(), vec!["The", "people"]);
+        conll2string.build("Output/dependency_inverse_sentence.txt").unwrap();
+        assert_eq!(conll2string.get_conll(), example);
+    }
+
+    #[test]
+    fn build_append() {
+
+        let save_to = "Output/dependency_corpus.txt";
+        let _ = std::fs::remove_file(save_to);
+        let examples = [
+            vec!["0	The	the	DET	_	_	1	det	_	_", "1	people	people	NOUN	_	_	1	ROOT	_	_"],
+            vec!["0	Hello	hello	INTJ	_	_	0	ROOT	_	_"]
+        ];
+        for example in examples.iter() {
+            let mut dependency = example.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+            let mut string2conll: String2Conll = String2StructureBuilder::new();
+            string2conll.build(&mut dependency).unwrap();
+            let mut conll2string: Conll2String = Structure2PlotBuilder::new(string2conll.get_structure());
+            conll2string.build_append(save_to).unwrap();
+        }
+
+        // the corpus is read back sentence by sentence
+        let mut reader = std::io::BufReader::new(std::fs::File::open(save_to).unwrap());
+        for example in examples.iter() {
+            let string2conll = String2Conll::from_reader(&mut reader).unwrap().unwrap();
+            assert_eq!(string2conll.get_structure().len(), example.len());
+        }
+        assert!(String2Conll::from_reader(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn aligned() {
+
+        let example = [
+            "# text = Déjà vu",
+            "0	Déjà	déjà	ADV	_	_	1	advmod	_	_",
+            "1	vu	voir	VERB	_	_	1	ROOT	_	SpaceAfter=No"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut dependency = example.clone();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        // comments are kept as is, and columns are aligned by characters rather than bytes
+        let mut conll2string = Conll2String::from(string2conll.get_sentence());
+        conll2string.set_aligned(true);
+        conll2string.build("Output/dependency_aligned_sentence.txt").unwrap();
+        assert_eq!(conll2string.get_conll(), vec![
+            "# text = Déjà vu",
+            "0  Déjà  déjà  ADV   _  _  1  advmod  _  _",
+            "1  vu    voir  VERB  _  _  1  ROOT    _  SpaceAfter=No"
+        ]);
+    }
 
     fn inverse_check(example: Vec<String>, save_to: String) -> Vec<String> { 
 
@@ -0,0 +1,617 @@
+//
+// Under MIT license
+//
+
+use std::collections::{HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use super::string_2_conll::{Token, UNATTACHED_HEAD, VIRTUAL_ROOT_HEAD};
+
+const SPACE_AFTER: &str = "SpaceAfter";
+
+/// An enum of the violations that make a dependency parse an invalid tree, see validate_tree.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeError {
+    /// The number of roots is not the expected one (expected, ids of the roots found).
+    RootCount(usize, Vec<f32>),
+    /// The chains of heads of the tokens lead into a cycle (ids of the tokens).
+    Cycle(Vec<f32>),
+    /// The tokens are not reachable from a root, their chain of heads ends in a head that is not a token (ids of the tokens).
+    Unreachable(Vec<f32>)
+}
+
+impl fmt::Display for TreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TreeError::RootCount(expected, roots) => write!(f, "expected {} roots, found {} {:?}", expected, roots.len(), roots),
+            TreeError::Cycle(ids) => write!(f, "the heads of tokens {:?} form a cycle", ids),
+            TreeError::Unreachable(ids) => write!(f, "tokens {:?} are not reachable from a root", ids)
+        }
+    }
+}
+
+impl Error for TreeError {}
+
+// A helper that finds a token by its id (not by its position in the slice).
+fn find_token(tokens: &[Token], id: f32) -> Option<&Token> {
+    tokens.iter().find(|token| token.get_token_id() == id)
+}
+
+///
+/// A method that returns the chain of heads of a token, from its head up to the root (the token itself is excluded).
+/// The chain stops at the root (a token that is its own head), at a head that is not found in the tokens,
+/// or when a cycle is detected. Returns an empty vector if the id is not found or if the token is the root.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::token_ancestors;
+///
+/// let mut dependency = [
+///     "0	The	the	DET	_	_	1	det	_	_",
+///     "1	people	people	NOUN	_	_	2	nsubj	_	_",
+///     "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+///     "3	the	the	DET	_	_	4	det	_	_",
+///     "4	game	game	NOUN	_	_	2	dobj	_	_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+/// let conll = string2conll.get_structure();
+///
+/// let ancestors = token_ancestors(&conll, 0.0).iter().map(|x| x.get_token_form()).collect::<Vec<String>>();
+/// assert_eq!(ancestors, vec!["people", "watch"]);
+/// ```
+///
+pub fn token_ancestors(tokens: &[Token], id: f32) -> Vec<&Token> {
+
+    let mut ancestors = Vec::new();
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut current = match find_token(tokens, id) {
+        Some(token) => token,
+        None => return ancestors
+    };
+    visited.insert(current.get_token_id().to_bits());
+
+    // follow the head pointers until the root, a missing head or a cycle
+    while current.get_token_head() != current.get_token_id() {
+        let head = match find_token(tokens, current.get_token_head()) {
+            Some(head) => head,
+            None => break
+        };
+        if !visited.insert(head.get_token_id().to_bits()) {
+            break;
+        }
+        ancestors.push(head);
+        current = head;
+    }
+
+    ancestors
+}
+
+///
+/// A method that returns the subtree of a token, the token itself and all of its transitive dependents, in the
+/// order of the tokens (so the forms of a subtree give its phrase). Dependents are found by a breadth first search
+/// over the head relation, that visits every token once, so a cycle does not loop. Returns an empty vector if the
+/// id is not found.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::token_subtree;
+///
+/// let mut dependency = [
+///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+///     "1\tpeople\tpeople\tNOUN\t_\t_\t2\tnsubj\t_\t_",
+///     "2\twatch\twatch\tVERB\t_\t_\t2\tROOT\t_\t_",
+///     "3\tthe\tthe\tDET\t_\t_\t4\tdet\t_\t_",
+///     "4\tgame\tgame\tNOUN\t_\t_\t2\tdobj\t_\t_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+/// let conll = string2conll.get_structure();
+///
+/// let phrase = token_subtree(&conll, 4.0).iter().map(|x| x.get_token_form()).collect::<Vec<String>>();
+/// assert_eq!(phrase, vec!["the", "game"]);
+/// ```
+///
+pub fn token_subtree(tokens: &[Token], id: f32) -> Vec<&Token> {
+
+    let mut visited: HashSet<u32> = HashSet::new();
+    let mut queue = match find_token(tokens, id) {
+        Some(token) => VecDeque::from([token]),
+        None => return Vec::new()
+    };
+    visited.insert(id.to_bits());
+
+    // the dependents of a token are the other tokens that it heads, the root is not a dependent of itself
+    while let Some(current) = queue.pop_front() {
+        for token in tokens.iter().filter(|token| token.get_token_head() == current.get_token_id()) {
+            if visited.insert(token.get_token_id().to_bits()) {
+                queue.push_back(token);
+            }
+        }
+    }
+
+    tokens.iter()
+    .filter(|token| visited.contains(&token.get_token_id().to_bits()))
+    .collect::<Vec<&Token>>()
+}
+
+///
+/// A method that returns the depth of every token, i.e the number of head hops from the token to the root.
+/// The root has depth 0. The (id, depth) pairs are in the order of the tokens, so decimal ids are kept as they are.
+/// Tokens whose chain is broken by a missing head or a cycle get the length of the chain that was found.
+///
+pub fn token_depths(tokens: &[Token]) -> Vec<(f32, usize)> {
+    tokens.iter()
+    .map(|token| (token.get_token_id(), token_ancestors(tokens, token.get_token_id()).len()))
+    .collect::<Vec<(f32, usize)>>()
+}
+
+///
+/// A method that returns the orphan tokens, whose head does not match any token id. An orphan is not the root
+/// (a token that is its own head), and is drawn without an arc, which may point to a problem in the data.
+/// Unattached tokens (an empty head field) are not orphans, since they are marked as such on purpose, and neither
+/// are tokens whose head is a virtual root 0 (as in UD).
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::orphan_tokens;
+///
+/// let mut dependency = [
+///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+///     "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_",
+///     "2\twatch\twatch\tVERB\t_\t_\t7\tdep\t_\t_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+/// let conll = string2conll.get_structure();
+///
+/// let orphans = orphan_tokens(&conll).iter().map(|x| x.get_token_form()).collect::<Vec<String>>();
+/// assert_eq!(orphans, vec!["watch"]);
+/// ```
+///
+pub fn orphan_tokens(tokens: &[Token]) -> Vec<&Token> {
+    tokens.iter()
+    .filter(|token| ![token.get_token_id(), UNATTACHED_HEAD, VIRTUAL_ROOT_HEAD].contains(&token.get_token_head()))
+    .filter(|token| find_token(tokens, token.get_token_head()).is_none())
+    .collect::<Vec<&Token>>()
+}
+
+///
+/// A method that returns the head-dependent pairs of a dependency parse as an edge list of (head id, dependent id,
+/// deprel), in the order of the dependents. The root (a token that is its own head, or whose head is a virtual
+/// root 0 as in UD) has no edge, and neither do unattached and orphan tokens, so every edge is between two tokens.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::dependency_edges;
+///
+/// let mut dependency = [
+///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+///     "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+///
+/// assert_eq!(dependency_edges(&string2conll.get_structure()), vec![(1.0, 0.0, "det".to_string())]);
+/// ```
+///
+pub fn dependency_edges(tokens: &[Token]) -> Vec<(f32, f32, String)> {
+    tokens.iter()
+    .filter(|token| !is_root(tokens, token))
+    .filter(|token| find_token(tokens, token.get_token_head()).is_some())
+    .map(|token| (token.get_token_head(), token.get_token_id(), token.get_token_deprel()))
+    .collect::<Vec<(f32, f32, String)>>()
+}
+
+///
+/// A method that compares a predicted parse to a gold parse of the same sentence, and returns whether every predicted
+/// token agrees with the gold token of the same id, i.e both its head and its deprel are the same, in the order of
+/// the predicted tokens. Returns an error if the parses do not have the same ids with the same forms.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::parse_agreement;
+///
+/// let mut gold = vec!["0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_".to_string(), "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_".to_string()];
+/// let mut predicted = vec!["0\tThe\tthe\tDET\t_\t_\t1\tamod\t_\t_".to_string(), "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_".to_string()];
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut gold).unwrap();
+/// let gold = string2conll.get_structure();
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut predicted).unwrap();
+///
+/// assert_eq!(parse_agreement(&gold, &string2conll.get_structure()).unwrap(), vec![false, true]);
+/// ```
+///
+pub fn parse_agreement(gold: &[Token], predicted: &[Token]) -> Result<Vec<bool>, Box<dyn Error>> {
+    if gold.len() != predicted.len() {
+        return Err(format!("the gold parse has {} tokens and the predicted parse has {}", gold.len(), predicted.len()).into());
+    }
+    predicted.iter().map(|token| {
+        let gold_token = find_token(gold, token.get_token_id())
+        .filter(|gold_token| gold_token.get_token_form() == token.get_token_form())
+        .ok_or(format!("token {} ({}) is not in the gold parse", token.get_token_id(), token.get_token_form()))?;
+        Ok(gold_token.get_token_head() == token.get_token_head() && gold_token.get_token_deprel() == token.get_token_deprel())
+    }).collect::<Result<Vec<bool>, Box<dyn Error>>>()
+}
+
+// A helper that returns the value of the given key in the misc field of a token, whose entries are "key=value"
+// pairs separated by "|". Returns None if the key is missing.
+pub(in crate) fn misc_value(token: &Token, key: &str) -> Option<String> {
+    token.get_token_misc().split('|').find_map(|entry| match entry.split_once('=') {
+        Some((entry_key, value)) if entry_key == key => Some(value.to_string()),
+        _ => None
+    })
+}
+
+// A helper that checks whether a token is followed by a space, i.e its misc field has no "SpaceAfter=No" entry.
+fn space_after(token: &Token) -> bool {
+    misc_value(token, SPACE_AFTER).as_deref() != Some("No")
+}
+
+///
+/// A method that reconstructs the text of a sentence from its tokens (as in the "# text = " comment of CoNLL-U),
+/// and returns it with the character span of every token in it. The forms are joined by a single space, except
+/// after tokens whose misc field has "SpaceAfter=No". The spans are (start, end) character offsets with an
+/// exclusive end, in the order of the tokens.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::text_with_offsets;
+///
+/// let mut dependency = [
+///     "0\tHello\thello\tINTJ\t_\t_\t0\troot\t_\tSpaceAfter=No",
+///     "1\t,\t,\tPUNCT\t_\t_\t0\tpunct\t_\t_",
+///     "2\tworld\tworld\tNOUN\t_\t_\t0\tvocative\t_\t_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+///
+/// let (text, offsets) = text_with_offsets(&string2conll.get_structure());
+/// assert_eq!(text, "Hello, world");
+/// assert_eq!(offsets, vec![(0, 5), (5, 6), (7, 12)]);
+/// ```
+///
+pub fn text_with_offsets(tokens: &[Token]) -> (String, Vec<(usize, usize)>) {
+
+    let mut text = String::new();
+    let mut offsets = Vec::new();
+    let mut position = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        let form = token.get_token_form();
+        let end = position + form.chars().count();
+        text.push_str(&form);
+        offsets.push((position, end));
+        position = end;
+
+        // no space is added after the last token
+        if i + 1 < tokens.len() && space_after(token) {
+            text.push(' ');
+            position += 1;
+        }
+    }
+    (text, offsets)
+}
+
+// A helper that checks whether a token is a root, i.e its own head, or attached to a virtual root 0 as in UD.
+fn is_root(tokens: &[Token], token: &Token) -> bool {
+    token.get_token_head() == token.get_token_id()
+    || (token.get_token_head() == VIRTUAL_ROOT_HEAD && find_token(tokens, VIRTUAL_ROOT_HEAD).is_none())
+}
+
+// A helper that checks whether the chain of heads of a token ends in a cycle. The chain of ancestors stops
+// at the root, at a missing head, or before a repeated token, so only in a cycle is the head of its last token found.
+pub(in crate) fn in_cycle(tokens: &[Token], token: &Token) -> bool {
+    let ancestors = token_ancestors(tokens, token.get_token_id());
+    let last = ancestors.last().copied().unwrap_or(token);
+    last.get_token_head() != last.get_token_id() && find_token(tokens, last.get_token_head()).is_some()
+}
+
+///
+/// A method that checks whether a dependency parse is a well formed tree: it has exactly one root (a token that
+/// is its own head, or whose head is a virtual root 0 as in UD), no chain of heads leads into a cycle, and every
+/// token is reachable from the root. The first violation found is returned, in that order.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Conll;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::{validate_tree, TreeError};
+///
+/// let mut dependency = [
+///     "0\tThe\tthe\tDET\t_\t_\t1\tdet\t_\t_",
+///     "1\tpeople\tpeople\tNOUN\t_\t_\t1\tROOT\t_\t_",
+///     "2\twatch\twatch\tVERB\t_\t_\t7\tdep\t_\t_"
+/// ].map(|x| x.to_string()).to_vec();
+///
+/// let mut string2conll: String2Conll = String2StructureBuilder::new();
+/// string2conll.build(&mut dependency).unwrap();
+///
+/// assert_eq!(validate_tree(&string2conll.get_structure()), Err(TreeError::Unreachable(vec![2.0])));
+/// ```
+///
+pub fn validate_tree(tokens: &[Token]) -> Result<(), TreeError> {
+    validate_forest(tokens, 1)
+}
+
+///
+/// A method that checks whether a dependency parse is a well formed forest of the given number of trees,
+/// as validate_tree does for a single tree.
+///
+pub fn validate_forest(tokens: &[Token], n_roots: usize) -> Result<(), TreeError> {
+
+    let roots = tokens.iter().filter(|token| is_root(tokens, token)).map(|token| token.get_token_id()).collect::<Vec<f32>>();
+    if roots.len() != n_roots {
+        return Err(TreeError::RootCount(n_roots, roots));
+    }
+
+    let cycle = tokens.iter().filter(|token| in_cycle(tokens, token)).map(|token| token.get_token_id()).collect::<Vec<f32>>();
+    if !cycle.is_empty() {
+        return Err(TreeError::Cycle(cycle));
+    }
+
+    // without cycles, the chain of heads of a token ends either at a root or at a head that is not a token
+    let unreachable = tokens.iter().filter(|token| {
+        let ancestors = token_ancestors(tokens, token.get_token_id());
+        !is_root(tokens, ancestors.last().copied().unwrap_or(token))
+    }).map(|token| token.get_token_id()).collect::<Vec<f32>>();
+    if !unreachable.is_empty() {
+        return Err(TreeError::Unreachable(unreachable));
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{token_ancestors, token_subtree, token_depths, orphan_tokens, dependency_edges, parse_agreement, text_with_offsets, validate_tree, validate_forest, TreeError};
+    use crate::{String2StructureBuilder, String2Conll};
+
+    fn conll_template(example: Vec<&str>) -> Vec<crate::string_2_conll::Token> {
+        let mut dependency = example.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        string2conll.get_structure()
+    }
+
+    #[test]
+    fn ancestors() {
+        let conll = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ]);
+
+        let prediction = token_ancestors(&conll, 3.0).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![4.0, 2.0]);
+        assert!(token_ancestors(&conll, 2.0).is_empty());
+        assert!(token_ancestors(&conll, 7.0).is_empty());
+    }
+
+    #[test]
+    fn subtree() {
+        let conll = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ]);
+
+        let prediction = token_subtree(&conll, 2.0).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        let prediction = token_subtree(&conll, 1.0).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![0.0, 1.0]);
+        assert_eq!(token_subtree(&conll, 0.0).len(), 1);
+        assert!(token_subtree(&conll, 7.0).is_empty());
+
+        // a cycle is visited once
+        let conll = conll_template(vec![
+            "0	a	a	X	_	_	1	dep	_	_",
+            "1	b	b	X	_	_	0	dep	_	_",
+            "2	c	c	X	_	_	1	dep	_	_"
+        ]);
+        let prediction = token_subtree(&conll, 0.0).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn depths() {
+        let conll = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ]);
+
+        let golden = vec![(0.0, 2), (1.0, 1), (2.0, 0), (3.0, 2), (4.0, 1)];
+        assert_eq!(token_depths(&conll), golden);
+
+        // a decimal id is not merged with the id it follows
+        let conll = conll_template(vec![
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "1.1	also	also	ADV	_	_	1	advmod	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ]);
+        assert_eq!(token_depths(&conll), vec![(1.0, 1), (1.1, 2), (2.0, 0)]);
+    }
+
+    #[test]
+    fn ancestors_cycle() {
+        let conll = conll_template(vec![
+            "0	a	a	X	_	_	1	dep	_	_",
+            "1	b	b	X	_	_	2	dep	_	_",
+            "2	c	c	X	_	_	0	dep	_	_"
+        ]);
+
+        let prediction = token_ancestors(&conll, 0.0).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![1.0, 2.0]);
+    }
+
+    #[test]
+    fn orphans() {
+        let conll = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	9	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	_	det	_	_",
+            "4	game	game	NOUN	_	_	5	dobj	_	_"
+        ]);
+
+        let prediction = orphan_tokens(&conll).iter().map(|x| x.get_token_id()).collect::<Vec<f32>>();
+        assert_eq!(prediction, vec![1.0, 4.0]);
+
+        // the root of UD points at a virtual root 0
+        let conll = conll_template(vec![
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	0	root	_	_"
+        ]);
+        assert!(orphan_tokens(&conll).is_empty());
+    }
+
+    #[test]
+    fn edges() {
+        let conll = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ]);
+
+        let prediction = dependency_edges(&conll);
+        let expected = vec![(1.0, 0.0, "det"), (2.0, 1.0, "nsubj"), (4.0, 3.0, "det"), (2.0, 4.0, "dobj")]
+        .into_iter().map(|(head, dependent, deprel)| (head, dependent, deprel.to_string())).collect::<Vec<(f32, f32, String)>>();
+        assert_eq!(prediction, expected);
+
+        // the virtual root of UD gives no edge either, nor does an orphan
+        let conll = conll_template(vec![
+            "1	Hello	hello	INTJ	_	_	0	root	_	_",
+            "2	world	world	NOUN	_	_	1	vocative	_	_",
+            "3	!	!	PUNCT	_	_	7	punct	_	_"
+        ]);
+        assert_eq!(dependency_edges(&conll), vec![(1.0, 2.0, "vocative".to_string())]);
+    }
+
+    #[test]
+    fn agreement() {
+        let gold = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ]);
+        let predicted = conll_template(vec![
+            "0	The	the	DET	_	_	2	det	_	_",
+            "1	people	people	NOUN	_	_	2	obj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ]);
+        assert_eq!(parse_agreement(&gold, &predicted).unwrap(), vec![false, false, true]);
+        assert_eq!(parse_agreement(&gold, &gold).unwrap(), vec![true; 3]);
+
+        // the parses must be of the same sentence
+        assert!(parse_agreement(&gold, &predicted[..2]).is_err());
+        let other = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watched	watch	VERB	_	_	2	ROOT	_	_"
+        ]);
+        assert!(parse_agreement(&gold, &other).is_err());
+    }
+
+    #[test]
+    fn offsets() {
+        // the reconstruction of a UD sentence matches its text comment
+        let mut dependency = std::fs::read_to_string("Input/conll_ud.txt").unwrap().lines().map(|x| x.to_string()).collect::<Vec<String>>();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let sentence = string2conll.get_sentence();
+
+        let (text, offsets) = text_with_offsets(sentence.get_tokens());
+        assert_eq!(Some(text.clone()), sentence.get_text());
+        let forms = offsets.iter().map(|(start, end)| text.chars().skip(*start).take(end - start).collect::<String>()).collect::<Vec<String>>();
+        assert_eq!(forms, sentence.get_forms());
+        assert_eq!(offsets.last(), Some(&(36, 37)));
+
+        // offsets count chars, not bytes
+        let conll = conll_template(vec![
+            "0	Café	café	NOUN	_	_	0	root	_	SpaceAfter=No|Foo=Bar",
+            "1	!	!	PUNCT	_	_	0	punct	_	_"
+        ]);
+        assert_eq!(text_with_offsets(&conll), ("Café!".to_string(), vec![(0, 4), (4, 5)]));
+    }
+
+    #[test]
+    fn validate() {
+        let valid = conll_template(vec![
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_"
+        ]);
+        assert_eq!(validate_tree(&valid), Ok(()));
+
+        // UD, the root is attached to a virtual root 0
+        let ud = conll_template(vec![
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	0	root	_	_"
+        ]);
+        assert_eq!(validate_tree(&ud), Ok(()));
+
+        let two_roots = conll_template(vec![
+            "0	Hello	hello	INTJ	_	_	0	ROOT	_	_",
+            "1	world	world	NOUN	_	_	1	ROOT	_	_"
+        ]);
+        assert_eq!(validate_tree(&two_roots), Err(TreeError::RootCount(1, vec![0.0, 1.0])));
+        assert_eq!(validate_forest(&two_roots, 2), Ok(()));
+
+        // 3 hangs off the cycle of 0 and 1, 4 is unattached
+        let cycle = conll_template(vec![
+            "0	a	a	X	_	_	1	dep	_	_",
+            "1	b	b	X	_	_	0	dep	_	_",
+            "2	c	c	X	_	_	2	ROOT	_	_",
+            "3	d	d	X	_	_	0	dep	_	_",
+            "4	e	e	X	_	_	_	_	_	_"
+        ]);
+        let error = validate_tree(&cycle).unwrap_err();
+        assert_eq!(error, TreeError::Cycle(vec![0.0, 1.0, 3.0]));
+        assert_eq!(error.to_string(), "the heads of tokens [0.0, 1.0, 3.0] form a cycle");
+
+        let unreachable = conll_template(vec![
+            "0	a	a	X	_	_	0	ROOT	_	_",
+            "1	b	b	X	_	_	7	dep	_	_",
+            "2	c	c	X	_	_	1	dep	_	_",
+            "3	d	d	X	_	_	_	_	_	_"
+        ]);
+        assert_eq!(validate_tree(&unreachable), Err(TreeError::Unreachable(vec![1.0, 2.0, 3.0])));
+    }
+
+}
@@ -0,0 +1,169 @@
+//
+// Under MIT license
+//
+
+use id_tree::Tree;
+use std::error::Error;
+
+use super::string_2_conll::Token;
+use super::conll_analysis::dependency_edges;
+use super::tree_2_plot::Tree2Plot;
+use super::tree_2_string::Tree2String;
+use super::conll_2_plot::Conll2Plot;
+use super::conll_2_string::Conll2String;
+use super::generic_traits::generic_traits::Structure2PlotBuilder;
+
+const IMG_TYPE: &str = ".png";
+const TXT_TYPE: &str = ".txt";
+
+///
+/// A method that saves both the plot of a constituency tree and its reconstructed string, side by side.
+/// The output path is given without an extension, the image is saved to save_to.png and the string to save_to.txt.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::export_tree;
+///
+/// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+/// let tree = string2tree.get_structure();
+///
+/// export_tree(tree, "Output/constituency_export").unwrap();
+/// ```
+///
+pub fn export_tree(tree: Tree<String>, save_to: &str) -> Result<(), Box<dyn Error>> {
+
+    let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(tree.clone());
+    tree2plot.build(&(save_to.to_string() + IMG_TYPE))?;
+
+    let mut tree2string: Tree2String = Structure2PlotBuilder::new(tree);
+    tree2string.build(&(save_to.to_string() + TXT_TYPE))?;
+    Ok(())
+}
+
+///
+/// A method that saves both the plot of a dependency and its reconstructed conll, side by side.
+/// The output path is given without an extension, the image is saved to save_to.png and the conll to save_to.txt.
+///
+pub fn export_conll(tokens: Vec<Token>, save_to: &str) -> Result<(), Box<dyn Error>> {
+
+    let mut conll2plot: Conll2Plot = Structure2PlotBuilder::new(tokens.clone());
+    conll2plot.build(&(save_to.to_string() + IMG_TYPE))?;
+
+    let mut conll2string: Conll2String = Structure2PlotBuilder::new(tokens);
+    conll2string.build(&(save_to.to_string() + TXT_TYPE))?;
+    Ok(())
+}
+
+// A helper that quotes a label as a dot id, escaping quotes and backslashes.
+fn dot_id(label: &str) -> String {
+    format!("\"{}\"", label.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+///
+/// A method that writes a constituency tree in the dot language of graphviz, as a digraph with an edge from every
+/// node to each of its children. The nodes are named by their pre order index and labeled by their labels.
+/// Returns an error if the tree is empty.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::tree_to_dot;
+///
+/// let mut constituency = String::from("(S (NP Hello))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+///
+/// let dot = tree_to_dot(&string2tree.get_structure()).unwrap();
+/// assert_eq!(dot, "digraph {\n    0 [label=\"S\"];\n    1 [label=\"NP\"];\n    2 [label=\"Hello\"];\n    0 -> 1;\n    1 -> 2;\n}\n");
+/// ```
+///
+pub fn tree_to_dot(tree: &Tree<String>) -> Result<String, Box<dyn Error>> {
+
+    let root_id = tree.root_node_id().ok_or("the tree is empty")?;
+    let node_ids = tree.traverse_pre_order_ids(root_id)?.collect::<Vec<_>>();
+
+    let mut dot = String::from("digraph {\n");
+    for (i, node_id) in node_ids.iter().enumerate() {
+        dot.push_str(&format!("    {} [label={}];\n", i, dot_id(tree.get(node_id)?.data())));
+    }
+    for (i, node_id) in node_ids.iter().enumerate() {
+        for child_id in tree.get(node_id)?.children() {
+            let child = node_ids.iter().position(|x| x == child_id).ok_or("a child is not in the tree")?;
+            dot.push_str(&format!("    {} -> {};\n", i, child));
+        }
+    }
+    dot.push_str("}\n");
+    Ok(dot)
+}
+
+///
+/// A method that writes a dependency in the dot language of graphviz, as a digraph with an edge from every head to
+/// its dependent, labeled by the deprel. The nodes are named by the index of the token and labeled by its form.
+/// The root and tokens without a head in the sentence have no incoming edge, as in dependency_edges.
+///
+pub fn conll_to_dot(tokens: &[Token]) -> String {
+
+    let mut dot = String::from("digraph {\n");
+    for (i, token) in tokens.iter().enumerate() {
+        dot.push_str(&format!("    {} [label={}];\n", i, dot_id(&token.get_token_form())));
+    }
+    let index_of = |id: f32| tokens.iter().position(|token| token.get_token_id() == id);
+    for (head, dependent, deprel) in dependency_edges(tokens) {
+        if let (Some(head), Some(dependent)) = (index_of(head), index_of(dependent)) {
+            dot.push_str(&format!("    {} -> {} [label={}];\n", head, dependent, dot_id(&deprel)));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{export_conll, conll_to_dot, dot_id};
+    use crate::{String2StructureBuilder, String2Conll};
+
+    #[test]
+    fn conll_export() {
+        let example = [
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "4	game	game	NOUN	_	_	2	dobj	_	_"
+        ];
+        let mut dependency = example.map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        export_conll(string2conll.get_structure(), "Output/dependency_export").unwrap();
+        assert!(std::path::Path::new("Output/dependency_export.png").exists());
+        assert_eq!(std::fs::read_to_string("Output/dependency_export.txt").unwrap(), example.join("\n"));
+    }
+
+    #[test]
+    fn dot() {
+        let example = [
+            "1	\"Hi\"	hi	INTJ	_	_	2	discourse	_	_",
+            "1.1	there	there	ADV	_	_	_	_	_	_",
+            "2	all	all	PRON	_	_	2	ROOT	_	_"
+        ];
+        let mut dependency = example.map(|x| x.to_string()).to_vec();
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+
+        // the root and the unattached token have no incoming edge, the quotes of the form are escaped
+        let golden = "digraph {\n    0 [label=\"\\\"Hi\\\"\"];\n    1 [label=\"there\"];\n    2 [label=\"all\"];\n    2 -> 0 [label=\"discourse\"];\n}\n";
+        assert_eq!(conll_to_dot(&string2conll.get_structure()), golden);
+        assert_eq!(dot_id("a\\b"), "\"a\\\\b\"");
+    }
+
+}
@@ -0,0 +1,88 @@
+//
+// Under MIT license
+//
+
+use id_tree::Tree;
+use std::error::Error;
+
+use super::string_2_tree::{String2Tree, LeafStyle};
+use super::tree_2_plot::Tree2Plot;
+use super::generic_traits::generic_traits::{String2StructureBuilder, Structure2PlotBuilder};
+
+const OPERAND_COLOR: (u8, u8, u8) = (31, 119, 180);
+
+/// An Expr2Plot struct, a thin wrapper of Tree2Plot for mathematical expressions written with singular leaves,
+/// like "(* (+ (1) (2)) (3))". Inner nodes are operators and leaves are operands, the operands are drawn
+/// in a distinct color. The infix notation of an expression can be reconstructed with Tree2String::set_infix.
+#[derive(Clone)]
+pub struct Expr2Plot {
+    tree2plot: Tree2Plot
+}
+
+impl Expr2Plot {
+
+    ///
+    /// Initialization of an Expr2Plot object directly from an expression string. Returns an error if the
+    /// string has double leaves (it is not an expression).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parsed_to_plot::Expr2Plot;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    ///
+    /// let mut expr2plot = Expr2Plot::from_expression("(* (+ (1) (2)) (3))").unwrap();
+    /// expr2plot.build("Output/expression.png").unwrap();
+    /// assert!(Expr2Plot::from_expression("(S (NP (det The) (N people)))").is_err());
+    /// ```
+    ///
+    pub fn from_expression(expression: &str) -> Result<Self, Box<dyn Error>> {
+        let mut expression = expression.to_string();
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut expression)?;
+        if string2tree.get_leaf_style() != LeafStyle::Singular {
+            return Err("an expression should have singular leaves only, like (+ (1) (2))".into());
+        }
+        Ok(Structure2PlotBuilder::new(string2tree.get_structure()))
+    }
+
+    ///
+    /// A get method to the wrapped Tree2Plot, to set any of its plotting options.
+    ///
+    pub fn get_tree2plot(&mut self) -> &mut Tree2Plot {
+        &mut self.tree2plot
+    }
+}
+
+impl Structure2PlotBuilder<Tree<String>> for Expr2Plot {
+
+    fn new(structure: Tree<String>) -> Self {
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(structure);
+        tree2plot.set_leaf_color(Some(OPERAND_COLOR));
+        Self {
+            tree2plot
+        }
+    }
+
+    fn build(&mut self, save_to: &str) -> Result<(), Box<dyn Error>> {
+        self.tree2plot.build(save_to)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::Expr2Plot;
+    use crate::Structure2PlotBuilder;
+
+    #[test]
+    fn expression() {
+        let mut expr2plot = Expr2Plot::from_expression("(* (+ (1) (2)) (- (3)))").unwrap();
+        expr2plot.get_tree2plot().set_scale(2.0).unwrap();
+        expr2plot.build("Output/expression_scaled.png").unwrap();
+
+        assert!(Expr2Plot::from_expression("(S (0 (1) (2 x)))").is_err());
+    }
+
+}
@@ -9,10 +9,12 @@
 //! * For constituency trees, the program takes a parsed string given in one line. The string can be syntactic, for example
 //! such that represents phrases and parts-of-speech (like the structure in [Berkeley Neural Parser](https://pypi.org/project/benepar/)
 //! in python). Such strings will have "double leaves" (see an example below). Alternatively, the strings can have singular leaves,
-//! representing, for example, mathematical expressions.
+//! representing, for example, mathematical expressions (see Expr2Plot).
 //! * For dependency trees, the programs takes a conll format, in which every token has 10 fields, separated by tab, and
 //! presented in a new line. Sentences are separated by an empty line. (see an example below, using an output from
 //! [spaCy](https://spacy.io/) in python). 
+//! * A dependency can also be given in a compact single line, form/pos/head/deprel per token and separated by spaces,
+//!   like "The/DET/1/det people/NOUN/1/ROOT" (see String2Conll::build_compact).
 //! * For multiple inputs of the same type, the program expects 3 arguments from the command line :
 //!     * input type ("c" = constituency / "d" = dependency), String
 //!     * input file path, String
@@ -84,18 +86,28 @@
 //! conll2plot.build(save_to).unwrap();
 //! ```
 //! 
+//! The output format is determined by the extension of the path: paths ending with .svg are saved as svg,
+//! and paths ending with .pdf are saved as pdf when the crate is compiled with the pdf feature (via svg2pdf).
+//! Paths ending with .jpg or .jpeg are saved as jpeg, other paths are saved as bitmaps by their extension (usually .png).
+//! Constituency trees saved to .gif paths are animated, the tree is built node by node (see set_gif_frame_delay).
+//! The png compression level and the jpeg quality can be set with set_png_compression and set_jpeg_quality.
+//! Custom drawings (a highlight, an arrow) can be added over a plot with set_overlay, which hands the plotters chart to a callback before the image is saved.
+//! Texts are drawn in the sans-serif font of the system, for identical bitmaps on every machine a font can be embedded with set_font
+//! (a public domain font is bundled as BUNDLED_FONT with the bundled_font feature).
+//! 
 //! ## Multiple inputs via file 
 //! 
 //! You can use a combination of the API and command-line to process multiple inputs of the same type through a file.
 //! The command-line format is as follows:
 //! ```text
-//! cargo run INPUT_TYPE INPUT_FILE OUTPUT_PATH
+//! cargo run INPUT_TYPE INPUT_FILE OUTPUT_PATH [OUTPUT_FORMAT]
 //! ```
 //! 
 //! when:
 //! * INPUT_TYPE should be replaced with "c" for constituency or "d" for dependency.
 //! * INPUT_FILE should be replaced with a path to a txt file with inputs.
 //! * OUTPUT_PATH should be replaced with a path to a requested output dir.
+//! * OUTPUT_FORMAT is optional, "png" (default), "jpeg", "gif", "svg", "pdf" (with the pdf feature), "dot" for graphviz or "txt" for the reconstructed strings (see Config::build_all).
 //! 
 //! For example, you can enter multiple constituencies by using the following command:
 //! 
@@ -107,41 +119,29 @@
 //! 
 //! ```ignore
 //! use parsed_to_plot::Config;
-//! use parsed_to_plot::String2Tree;
-//! use parsed_to_plot::Tree2Plot;
-//! use parsed_to_plot::String2StructureBuilder;
-//! use parsed_to_plot::Structure2PlotBuilder;
 //! use std::env;
+//! use std::error::Error;
 //! 
-//! // collect arguments from command line 
-//! let args: Vec<String> = env::args().collect();
-//! // note: your command line args should translate to something similar to the following:
-//! // let args: Vec<String> = ["PROGRAM_NAME", "c", "Input/constituencies.txt", "ConOutput"].map(|x| x.to_string()).to_vec();
-//! 
-//! // run configuration protocol and inspectations
-//! let sequences = match Config::new(&args) {
-//!     Ok(sequences) => Vec::<String>::try_from(sequences).unwrap(),
-//!     Err(config) => panic!("{}", config) 
-//! };
-//! 
-//! for (i, mut constituency) in sequences.into_iter().enumerate() {
-//!            
-//!     println!("working on input number {} ...", i);
-//!     let save_to = &Config::get_out_file(&args[3], i.to_string().as_str());
-//!     
-//!     // build tree from consituency
-//!     let mut string2tree: String2Tree = String2StructureBuilder::new();
-//!     string2tree.build(&mut constituency).unwrap();
-//!     let tree = string2tree.get_structure();
-//!
-//!     // build plot from tree
-//!     let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(tree);
-//!     tree2plot.build(save_to).unwrap();
+//! fn main() -> Result<(), Box<dyn Error>> {
+//! 
+//!     // collect arguments from command line 
+//!     let args: Vec<String> = env::args().collect();
+//!     // note: your command line args should translate to something similar to the following:
+//!     // let args: Vec<String> = ["PROGRAM_NAME", "c", "Input/constituencies.txt", "ConOutput"].map(|x| x.to_string()).to_vec();
+//! 
+//!     // run configuration protocol and inspectations
+//!     let sequences = Config::new(&args)?;
+//! 
+//!     // build every input in the requested output format
+//!     Config::build_all(sequences, &args[3], Config::get_out_format(&args)?)
 //! }
 //! ```
 //! 
-//! Those will save png images of constituency trees drawn for the inputs in constituencies.txt, in an Output dir.
+//! Those will save images of constituency trees drawn for the inputs in constituencies.txt, in an Output dir,
+//! as png unless OUTPUT_FORMAT is given.
 //! The dependency equivalent is similar.
+//! When the crate is compiled with the flate2 feature, gzipped input files are decoded as well. They are recognized by their first bytes, regardless of the extension.
+//! For a quick preview of a large corpus, Config::head(&args, n) reads only the first n sentences of the input file.
 //! 
 //! ##  String reconstruction
 //! 
@@ -184,6 +184,9 @@
 //! assert_eq!(dependency_reproduction, example);
 //! ```
 //! 
+//! When the input spacing varies, equal_ignoring_whitespace compares the reconstruction to the input while ignoring incidental whitespace.
+//! Constituency labels that contain spaces or brackets can be escaped with a backslash, see set_escapes of String2Tree and Tree2String.
+//! 
 //! # References
 //! * I used the crates: [id-tree](https://crates.io/crates/id_tree), [plotters](https://crates.io/crates/plotters).
 //! * I used [spaCy](https://spacy.io/) to create a couple of dependency-parsed examples for illustration.
@@ -197,19 +200,49 @@ mod config;
 mod string_2_tree;
 mod string_2_conll;
 mod tree_2_plot;
+mod expr_2_plot;
 mod conll_2_plot;
 mod tree_2_string;
 mod conll_2_string;
 mod sub_tree_children;
+mod conll_analysis;
+mod tree_analysis;
+mod export;
 mod generic_traits;
 mod generic_enums;
+mod warnings;
+mod text_layout;
+mod tidy_layout;
+mod output_format;
+mod round_trip;
+mod parse_node;
 
-pub use config::Config;
+pub use config::{Config, OutputFormat};
+pub use config::{split_conllu, detect_input_type, auto_plot, InputType};
+pub use config::configure_structures::Saver;
 pub use string_2_tree::String2Tree;
+pub use string_2_tree::LeafStyle;
+pub use string_2_tree::{is_balanced, first_imbalance, is_balanced_escaped, first_imbalance_escaped};
 pub use string_2_conll::String2Conll;
+pub use string_2_conll::Sentence;
 pub use tree_2_plot::Tree2Plot;
+pub use tree_2_plot::TreeLayout;
+pub use tree_2_plot::EdgeStyle;
+pub use expr_2_plot::Expr2Plot;
 pub use conll_2_plot::Conll2Plot;
+pub use conll_2_plot::ColorBy;
+pub use conll_2_plot::TokenField;
+pub use output_format::{PngCompression, OverlayChart, OverlayCoord, BitmapPlotBackend};
+#[cfg(feature = "bundled_font")]
+pub use output_format::BUNDLED_FONT;
 pub use tree_2_string::Tree2String;
 pub use conll_2_string::Conll2String;
+pub use warnings::Warning;
+pub use text_layout::estimate_text_width;
+pub use conll_analysis::{token_ancestors, token_subtree, token_depths, orphan_tokens, dependency_edges, parse_agreement, text_with_offsets, validate_tree, validate_forest, TreeError};
+pub use tree_analysis::{root_to_leaf_paths, branching_factor, is_chain, node_span, span_f1};
+pub use export::{export_tree, export_conll, tree_to_dot, conll_to_dot};
+pub use parse_node::{to_parse_node, ParseNode};
+pub use round_trip::{normalize_whitespace, equal_ignoring_whitespace, normalize_constituency};
 pub use generic_traits::generic_traits::String2StructureBuilder;
 pub use generic_traits::generic_traits::Structure2PlotBuilder;
\ No newline at end of file
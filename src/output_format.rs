@@ -0,0 +1,607 @@
+//
+// Under MIT license
+//
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::Arc;
+use image::{ColorType, ImageEncoder};
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::codecs::jpeg::JpegEncoder;
+use ab_glyph::{Font, FontRef, InvalidFont, ScaleFont};
+use plotters::prelude::{BitMapBackend, SVGBackend, ChartContext, DrawingBackend, Cartesian2d};
+use plotters::coord::types::RangedCoordf32;
+use plotters_backend::{rasterizer, BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind, FontFamily, FontStyle, FontTransform};
+use plotters_backend::text_anchor::{HPos, VPos, Pos};
+
+const SVG_EXTENSION: &str = ".svg";
+const PDF_EXTENSION: &str = ".pdf";
+const PNG_EXTENSION: &str = ".png";
+const GIF_EXTENSION: &str = ".gif";
+const JPEG_EXTENSIONS: [&str; 2] = [".jpg", ".jpeg"];
+const BMP_EXTENSION: &str = ".bmp";
+const DEFAULT_JPEG_QUALITY: u8 = 75;
+const DEFAULT_GIF_FRAME_DELAY: u32 = 500;  // milliseconds
+const PNG_HEADER_END: usize = 33;  // the signature (8 bytes) and the IHDR chunk (25 bytes)
+const PNG_TEXT_CHUNK: &[u8; 4] = b"iTXt";
+const MAX_KEYWORD_LENGTH: usize = 79;
+const COVERAGE_THRESHOLD: f64 = 0.5;  // the minimal coverage of a pixel that is drawn without anti-aliasing
+
+/// A public domain font (Tuffy) that is bundled with the crate by the bundled_font feature, to be set by set_font.
+#[cfg(feature = "bundled_font")]
+pub const BUNDLED_FONT: &[u8] = include_bytes!("../fonts/Tuffy.ttf");
+
+/// An enum of the compression levels of png outputs, a trade off between the file size and the encoding time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PngCompression {
+    /// Minimal compression, the fastest encoding (the default).
+    Fast,
+    /// A balanced compression level.
+    Default,
+    /// The smallest files, the slowest encoding.
+    Best
+}
+
+// A struct of the encoding settings of bitmap outputs, held by the plotting modules.
+#[derive(Clone, Debug)]
+pub(in crate) struct EncodingOptions {
+    pub(in crate) png_compression: PngCompression,
+    pub(in crate) jpeg_quality: u8,
+    pub(in crate) gif_frame_delay: u32,
+    pub(in crate) antialiasing: bool,
+    pub(in crate) font: Option<&'static [u8]>,
+    pub(in crate) png_texts: Vec<(String, String)>
+}
+
+impl Default for EncodingOptions {
+    fn default() -> Self {
+        Self { png_compression: PngCompression::Fast, jpeg_quality: DEFAULT_JPEG_QUALITY, gif_frame_delay: DEFAULT_GIF_FRAME_DELAY, antialiasing: true, font: None, png_texts: Vec::new() }
+    }
+}
+
+// An enum of the output formats of the plotting modules, determined by the extension of the output path.
+// Png and jpeg are encoded by the crate with the encoding options, any other extension is left to the
+// bitmap backend of plotters (which determines the image format by the extension). Gif is drawn by the gif backend
+// of plotters, frame by frame. The output formats of the command line (config::OutputFormat) map to these formats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(in crate) enum PlotFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Svg,
+    Pdf,
+    Bitmap
+}
+
+impl PlotFormat {
+    pub(in crate) fn from_path(save_to: &str) -> Self {
+        let lowercase = save_to.to_lowercase();
+        if lowercase.ends_with(SVG_EXTENSION) {
+            PlotFormat::Svg
+        } else if lowercase.ends_with(PDF_EXTENSION) {
+            PlotFormat::Pdf
+        } else if lowercase.ends_with(PNG_EXTENSION) {
+            PlotFormat::Png
+        } else if lowercase.ends_with(GIF_EXTENSION) {
+            PlotFormat::Gif
+        } else if JPEG_EXTENSIONS.iter().any(|extension| lowercase.ends_with(extension)) {
+            PlotFormat::Jpeg
+        } else {
+            PlotFormat::Bitmap
+        }
+    }
+
+    // The extension of the files saved in the format, from_path of the extension returns the format.
+    pub(in crate) fn extension(&self) -> &'static str {
+        match self {
+            PlotFormat::Png => PNG_EXTENSION,
+            PlotFormat::Jpeg => JPEG_EXTENSIONS[0],
+            PlotFormat::Gif => GIF_EXTENSION,
+            PlotFormat::Svg => SVG_EXTENSION,
+            PlotFormat::Pdf => PDF_EXTENSION,
+            PlotFormat::Bitmap => BMP_EXTENSION
+        }
+    }
+}
+
+/// The coordinates of the charts of the plotting modules, both axes are continuous.
+pub type OverlayCoord = Cartesian2d<RangedCoordf32, RangedCoordf32>;
+
+/// An enum of the charts handed to an overlay callback (see set_overlay), after the plot is drawn and before it is saved.
+/// The variant is determined by the backend of the output format, svg and pdf outputs are drawn by the svg backend.
+/// A generic drawing function over DrawingBackend can serve both variants.
+pub enum OverlayChart<'c, 'a, 'b> {
+    /// the chart of png, jpeg, gif and other bitmap outputs.
+    Bitmap(&'c mut ChartContext<'a, BitmapPlotBackend<'b>, OverlayCoord>),
+    /// the chart of svg and pdf outputs.
+    Svg(&'c mut ChartContext<'a, SVGBackend<'b>, OverlayCoord>)
+}
+
+// A user callback that draws additional series over a plot, see set_overlay.
+pub(in crate) type ChartOverlay = Arc<dyn Fn(OverlayChart<'_, '_, '_>) -> Result<(), Box<dyn Error>> + Send + Sync>;
+
+// A trait of the backends used by the plotting modules, which wraps their charts for an overlay callback.
+pub(in crate) trait OverlayBackend<'b>: DrawingBackend + Sized {
+    fn overlay_chart<'c, 'a>(chart: &'c mut ChartContext<'a, Self, OverlayCoord>) -> OverlayChart<'c, 'a, 'b>;
+}
+
+impl<'b> OverlayBackend<'b> for BitmapPlotBackend<'b> {
+    fn overlay_chart<'c, 'a>(chart: &'c mut ChartContext<'a, Self, OverlayCoord>) -> OverlayChart<'c, 'a, 'b> {
+        OverlayChart::Bitmap(chart)
+    }
+}
+
+impl<'b> OverlayBackend<'b> for SVGBackend<'b> {
+    fn overlay_chart<'c, 'a>(chart: &'c mut ChartContext<'a, Self, OverlayCoord>) -> OverlayChart<'c, 'a, 'b> {
+        OverlayChart::Svg(chart)
+    }
+}
+
+/// The backend of the bitmap outputs of the plotting modules, a bitmap backend of plotters that draws by the
+/// anti-aliasing and font settings of the plotting module (see set_antialiasing and set_font).
+pub struct BitmapPlotBackend<'b> {
+    inner: BitmapBackends<'b>
+}
+
+// The bitmap backend of plotters, wrapped by the backends of the encoding options that are set.
+enum BitmapBackends<'b> {
+    Plain(BitMapBackend<'b>),
+    Aliased(AliasedBackend<BitMapBackend<'b>>),
+    Font(FontBackend<BitMapBackend<'b>>),
+    AliasedFont(FontBackend<AliasedBackend<BitMapBackend<'b>>>)
+}
+
+// Calls the same method on the backend of any variant of BitmapBackends.
+macro_rules! dispatch {
+    ($backends:expr, $backend:ident => $call:expr) => {
+        match $backends {
+            BitmapBackends::Plain($backend) => $call,
+            BitmapBackends::Aliased($backend) => $call,
+            BitmapBackends::Font($backend) => $call,
+            BitmapBackends::AliasedFont($backend) => $call
+        }
+    };
+}
+
+impl<'b> BitmapPlotBackend<'b> {
+    // Wraps the given backend by the anti-aliasing and font of the encoding options, the font is validated by set_font.
+    pub(in crate) fn new(backend: BitMapBackend<'b>, options: &EncodingOptions) -> Result<Self, Box<dyn Error>> {
+        let inner = match (options.antialiasing, options.font) {
+            (true, None) => BitmapBackends::Plain(backend),
+            (false, None) => BitmapBackends::Aliased(AliasedBackend::new(backend)),
+            (true, Some(font)) => BitmapBackends::Font(FontBackend::new(backend, font)?),
+            (false, Some(font)) => BitmapBackends::AliasedFont(FontBackend::new(AliasedBackend::new(backend), font)?)
+        };
+        Ok(Self { inner })
+    }
+}
+
+impl<'b> DrawingBackend for BitmapPlotBackend<'b> {
+    type ErrorType = <BitMapBackend<'b> as DrawingBackend>::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        dispatch!(&self.inner, backend => backend.get_size())
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.ensure_prepared())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.present())
+    }
+
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_pixel(point, color))
+    }
+
+    fn draw_line<S: BackendStyle>(&mut self, from: BackendCoord, to: BackendCoord, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_line(from, to, style))
+    }
+
+    fn draw_rect<S: BackendStyle>(&mut self, upper_left: BackendCoord, bottom_right: BackendCoord, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_rect(upper_left, bottom_right, style, fill))
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(&mut self, path: I, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_path(path, style))
+    }
+
+    fn draw_circle<S: BackendStyle>(&mut self, center: BackendCoord, radius: u32, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_circle(center, radius, style, fill))
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(&mut self, vert: I, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.fill_polygon(vert, style))
+    }
+
+    fn blit_bitmap(&mut self, pos: BackendCoord, dims: (u32, u32), src: &[u8]) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.blit_bitmap(pos, dims, src))
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(&mut self, text: &str, style: &TStyle, pos: BackendCoord) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&mut self.inner, backend => backend.draw_text(text, style, pos))
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(&self, text: &str, style: &TStyle) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        dispatch!(&self.inner, backend => backend.estimate_text_size(text, style))
+    }
+}
+
+// A backend that draws to another backend without anti-aliasing, used by the plotting modules when
+// set_antialiasing is disabled. Plotters blends the edges of lines, circles and glyphs by the portion of every
+// pixel they cover, which may differ slightly across platforms. This backend draws every pixel that is at least
+// half covered in the full color of the shape, and skips the rest, so the output holds only the colors that were
+// drawn (translucent colors are kept as they are).
+struct AliasedBackend<DB: DrawingBackend> {
+    inner: DB,
+    alpha: f64  // the alpha of the shape that is currently drawn
+}
+
+impl<DB: DrawingBackend> AliasedBackend<DB> {
+    // Wraps the given backend, which receives the pixels that are drawn.
+    fn new(inner: DB) -> Self {
+        Self { inner, alpha: 1.0 }
+    }
+}
+
+impl<DB: DrawingBackend> DrawingBackend for AliasedBackend<DB> {
+    type ErrorType = DB::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.inner.get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.present()
+    }
+
+    // the rasterizers of plotters hand the coverage of every pixel as a fraction of the alpha of the shape
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        match color.alpha >= self.alpha * COVERAGE_THRESHOLD {
+            true => self.inner.draw_pixel(point, BackendColor { alpha: self.alpha, rgb: color.rgb }),
+            false => Ok(())
+        }
+    }
+
+    fn draw_line<S: BackendStyle>(&mut self, from: BackendCoord, to: BackendCoord, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.alpha = style.color().alpha;
+        rasterizer::draw_line(self, from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(&mut self, upper_left: BackendCoord, bottom_right: BackendCoord, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.alpha = style.color().alpha;
+        rasterizer::draw_rect(self, upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_circle<S: BackendStyle>(&mut self, center: BackendCoord, radius: u32, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.alpha = style.color().alpha;
+        rasterizer::draw_circle(self, center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(&mut self, vert: I, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.alpha = style.color().alpha;
+        let vertices = vert.into_iter().collect::<Vec<_>>();
+        rasterizer::fill_polygon(self, &vertices[..], style)
+    }
+
+    // the default text drawing of plotters, with the glyphs drawn by draw_pixel above
+    fn draw_text<TStyle: BackendTextStyle>(&mut self, text: &str, style: &TStyle, pos: BackendCoord) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.alpha = style.color().alpha;
+        if self.alpha == 0.0 {
+            return Ok(());
+        }
+
+        let ((min_x, min_y), (max_x, max_y)) = style.layout_box(text).map_err(|e| DrawingErrorKind::FontError(Box::new(e)))?;
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        let dx = match style.anchor().h_pos {
+            HPos::Left => 0,
+            HPos::Right => -width,
+            HPos::Center => -width / 2
+        };
+        let dy = match style.anchor().v_pos {
+            VPos::Top => 0,
+            VPos::Center => -height / 2,
+            VPos::Bottom => -height
+        };
+        let transform = style.transform();
+        let (w, h) = self.get_size();
+        let drawing_result = style.draw(text, (0, 0), |x, y, color| {
+            let (x, y) = transform.transform(x + dx - min_x, y + dy - min_y);
+            let (x, y) = (pos.0 + x, pos.1 + y);
+            match x >= 0 && x < w as i32 && y >= 0 && y < h as i32 {
+                true => self.draw_pixel((x, y), color),
+                false => Ok(())
+            }
+        });
+        drawing_result.map_err(|e| DrawingErrorKind::FontError(Box::new(e)))?
+    }
+}
+
+// A method that checks that the given bytes are a font that can be set by set_font (a TrueType or OpenType font).
+pub(in crate) fn validate_font(font: &'static [u8]) -> Result<(), Box<dyn Error>> {
+    FontRef::try_from_slice(font).map_err(|e| format!("invalid font: {}", e))?;
+    Ok(())
+}
+
+// A backend that draws the texts of another backend in an embedded font, used by the plotting modules when a font
+// is set (see set_font). By default, texts are drawn in the sans-serif font that plotters finds on the system, so the
+// same plot may look different across machines. The embedded font is used for all texts, regardless of their family
+// and style, and the rest of the drawing is left to the other backend.
+struct FontBackend<DB: DrawingBackend> {
+    inner: DB,
+    font: FontRef<'static>
+}
+
+impl<DB: DrawingBackend> FontBackend<DB> {
+    // Wraps the given backend, which receives the texts laid out in the given font (the bytes of a TrueType or
+    // OpenType font file). Fails if the bytes are not a valid font.
+    fn new(inner: DB, font: &'static [u8]) -> Result<Self, InvalidFont> {
+        Ok(Self { inner, font: FontRef::try_from_slice(font)? })
+    }
+}
+
+impl<DB: DrawingBackend> DrawingBackend for FontBackend<DB> {
+    type ErrorType = DB::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.inner.get_size()
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.present()
+    }
+
+    fn draw_pixel(&mut self, point: BackendCoord, color: BackendColor) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_pixel(point, color)
+    }
+
+    fn draw_line<S: BackendStyle>(&mut self, from: BackendCoord, to: BackendCoord, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_line(from, to, style)
+    }
+
+    fn draw_rect<S: BackendStyle>(&mut self, upper_left: BackendCoord, bottom_right: BackendCoord, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_rect(upper_left, bottom_right, style, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(&mut self, path: I, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_path(path, style)
+    }
+
+    fn draw_circle<S: BackendStyle>(&mut self, center: BackendCoord, radius: u32, style: &S, fill: bool) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_circle(center, radius, style, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(&mut self, vert: I, style: &S) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.fill_polygon(vert, style)
+    }
+
+    fn blit_bitmap(&mut self, pos: BackendCoord, dims: (u32, u32), src: &[u8]) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.blit_bitmap(pos, dims, src)
+    }
+
+    // the other backend lays out and draws the glyphs by the text style, whose font is replaced
+    fn draw_text<TStyle: BackendTextStyle>(&mut self, text: &str, style: &TStyle, pos: BackendCoord) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.draw_text(text, &EmbeddedTextStyle { style, font: &self.font }, pos)
+    }
+
+    fn estimate_text_size<TStyle: BackendTextStyle>(&self, text: &str, style: &TStyle) -> Result<(u32, u32), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.estimate_text_size(text, &EmbeddedTextStyle { style, font: &self.font })
+    }
+}
+
+// A text style that lays out and draws its glyphs in an embedded font, and takes the rest of its attributes from
+// another text style. The layout box starts at the top left corner of the text, its height is the line height of
+// the font (the ascent and the descent), and the glyphs are drawn relative to the same corner.
+struct EmbeddedTextStyle<'s, S: BackendTextStyle> {
+    style: &'s S,
+    font: &'s FontRef<'static>
+}
+
+impl<S: BackendTextStyle> BackendTextStyle for EmbeddedTextStyle<'_, S> {
+    type FontError = InvalidFont;
+
+    fn color(&self) -> BackendColor {
+        self.style.color()
+    }
+
+    fn size(&self) -> f64 {
+        self.style.size()
+    }
+
+    fn transform(&self) -> FontTransform {
+        self.style.transform()
+    }
+
+    fn style(&self) -> FontStyle {
+        self.style.style()
+    }
+
+    fn anchor(&self) -> Pos {
+        self.style.anchor()
+    }
+
+    fn family(&self) -> FontFamily<'_> {
+        self.style.family()
+    }
+
+    fn layout_box(&self, text: &str) -> Result<((i32, i32), (i32, i32)), Self::FontError> {
+        let font = self.font.as_scaled(self.size() as f32);
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let glyph_id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                width += font.kern(previous, glyph_id);
+            }
+            width += font.h_advance(glyph_id);
+            previous = Some(glyph_id);
+        }
+        Ok(((0, 0), (width.ceil() as i32, font.height().ceil() as i32)))
+    }
+
+    fn draw<E, DrawFunc: FnMut(i32, i32, BackendColor) -> Result<(), E>>(&self, text: &str, pos: BackendCoord, mut draw: DrawFunc) -> Result<Result<(), E>, Self::FontError> {
+        let font = self.font.as_scaled(self.size() as f32);
+        let color = self.color();
+        let mut x = pos.0 as f32;
+        let mut previous = None;
+        for c in text.chars() {
+            let mut glyph = font.scaled_glyph(c);
+            if let Some(previous) = previous {
+                x += font.kern(previous, glyph.id);
+            }
+            previous = Some(glyph.id);
+            let advance = font.h_advance(glyph.id);
+
+            // the glyph is placed on the baseline, an ascent below the top of the layout box
+            glyph.position = ab_glyph::point(x, pos.1 as f32 + font.ascent());
+            if let Some(outline) = font.outline_glyph(glyph) {
+                let bounds = outline.px_bounds();
+                let mut pixels = Vec::new();
+                outline.draw(|px, py, coverage| pixels.push((bounds.min.x as i32 + px as i32, bounds.min.y as i32 + py as i32, coverage)));
+                for (px, py, coverage) in pixels {
+                    if let Err(e) = draw(px, py, BackendColor { alpha: color.alpha * coverage as f64, rgb: color.rgb }) {
+                        return Ok(Err(e));
+                    }
+                }
+            }
+            x += advance;
+        }
+        Ok(Ok(()))
+    }
+}
+
+// A method that encodes a plot drawn to an rgb buffer as png or jpeg, by the given format and encoding options.
+// The image is encoded before the file is created, so a failed encoding leaves an existing file as it was.
+pub(in crate) fn save_bitmap(buffer: &[u8], dims: (u32, u32), save_to: &str, format: &PlotFormat, options: &EncodingOptions) -> Result<(), Box<dyn Error>> {
+
+    let mut encoded = Vec::new();
+    match format {
+        PlotFormat::Jpeg => {
+            let quality = options.jpeg_quality.clamp(1, 100);
+            JpegEncoder::new_with_quality(&mut encoded, quality).write_image(buffer, dims.0, dims.1, ColorType::Rgb8)?;
+        },
+        _ => {
+            let compression = match options.png_compression {
+                PngCompression::Fast => CompressionType::Fast,
+                PngCompression::Default => CompressionType::Default,
+                PngCompression::Best => CompressionType::Best
+            };
+            let mut png = Vec::new();
+            PngEncoder::new_with_quality(&mut png, compression, FilterType::Adaptive).write_image(buffer, dims.0, dims.1, ColorType::Rgb8)?;
+            encoded = with_text_chunks(png, &options.png_texts)?;
+        }
+    }
+    let mut writer = BufWriter::new(File::create(save_to)?);
+    writer.write_all(&encoded)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// A method that checks that a keyword of a png text chunk is 1-79 printable ascii characters, see add_png_text
+// (the png specification allows latin-1 keywords).
+pub(in crate) fn validate_png_keyword(keyword: &str) -> Result<(), Box<dyn Error>> {
+    if keyword.is_empty() || keyword.len() > MAX_KEYWORD_LENGTH || !keyword.chars().all(|c| (' '..='~').contains(&c)) {
+        return Err(format!("invalid png text keyword {:?}, expected 1-{} printable ascii characters", keyword, MAX_KEYWORD_LENGTH).into());
+    }
+    Ok(())
+}
+
+// A method that inserts the given (keyword, text) pairs as text chunks of an encoded png, right after its header.
+// The chunks are iTXt chunks, so the texts can hold any utf-8 characters. The keywords are validated as in
+// validate_png_keyword.
+fn with_text_chunks(png: Vec<u8>, texts: &[(String, String)]) -> Result<Vec<u8>, Box<dyn Error>> {
+
+    let mut chunks = Vec::new();
+    for (keyword, text) in texts {
+        validate_png_keyword(keyword)?;
+
+        // keyword, null separator, no compression (flag and method), empty language tag and translated keyword, text
+        let mut data = PNG_TEXT_CHUNK.to_vec();
+        data.extend_from_slice(keyword.as_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(text.as_bytes());
+
+        chunks.extend_from_slice(&((data.len() - PNG_TEXT_CHUNK.len()) as u32).to_be_bytes());
+        chunks.extend_from_slice(&data);
+        chunks.extend_from_slice(&crc32fast::hash(&data).to_be_bytes());
+    }
+
+    let mut png = png;
+    png.splice(PNG_HEADER_END..PNG_HEADER_END, chunks);
+    Ok(png)
+}
+
+// A method that converts a plot drawn as svg to a pdf file. Texts are converted to paths using the system fonts.
+#[cfg(feature = "pdf")]
+pub(in crate) fn save_pdf(svg: &str, save_to: &str) -> Result<(), Box<dyn Error>> {
+    use svg2pdf::usvg::{fontdb, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+
+    let mut tree = Tree::from_str(svg, &svg2pdf::usvg::Options::default())?;
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+
+    // the plots use the generic sans-serif family, fall back to any installed font if its default is missing
+    let sans_serif = fontdb::Query { families: &[fontdb::Family::SansSerif], ..fontdb::Query::default() };
+    if db.query(&sans_serif).is_none() {
+        let fallback = db.faces().find_map(|face| face.families.first().map(|(name, _)| name.clone()));
+        if let Some(family) = fallback {
+            db.set_sans_serif_family(family);
+        }
+    }
+    tree.postprocess(PostProcessingSteps::default(), &db);
+
+    std::fs::write(save_to, svg2pdf::convert_tree(&tree, svg2pdf::Options::default()))?;
+    Ok(())
+}
+
+// Without the pdf feature there is no converter, the svg is not saved either.
+#[cfg(not(feature = "pdf"))]
+pub(in crate) fn save_pdf(_svg: &str, save_to: &str) -> Result<(), Box<dyn Error>> {
+    Err(format!("saving {} requires the pdf feature of the crate", save_to).into())
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{PlotFormat, with_text_chunks};
+
+    #[test]
+    fn from_path() {
+        assert!(matches!(PlotFormat::from_path("Output/a.svg"), PlotFormat::Svg));
+        assert!(matches!(PlotFormat::from_path("Output/a.PDF"), PlotFormat::Pdf));
+        assert!(matches!(PlotFormat::from_path("Output/a.png"), PlotFormat::Png));
+        assert!(matches!(PlotFormat::from_path("Output/a.jpeg"), PlotFormat::Jpeg));
+        assert!(matches!(PlotFormat::from_path("Output/a.bmp"), PlotFormat::Bitmap));
+
+        for format in [PlotFormat::Png, PlotFormat::Jpeg, PlotFormat::Gif, PlotFormat::Svg, PlotFormat::Pdf, PlotFormat::Bitmap] {
+            assert_eq!(PlotFormat::from_path(&("Output/a".to_string() + format.extension())), format);
+        }
+    }
+
+    #[test]
+    fn text_chunks() {
+        // a minimal header, the chunks are inserted after it
+        let png = vec![0; 40];
+        let texts = vec![("Title".to_string(), "שלום".to_string())];
+        let png = with_text_chunks(png, &texts).unwrap();
+        assert_eq!(png.len(), 40 + 12 + 5 + 5 + "שלום".len());
+        assert_eq!(png[33..37], 18u32.to_be_bytes());
+        assert_eq!(png[37..46], *b"iTXtTitle");
+        assert_eq!(png[png.len() - 11..png.len() - 7], crc32fast::hash(&png[37..png.len() - 11]).to_be_bytes());
+
+        assert!(with_text_chunks(vec![0; 40], &[("".to_string(), "text".to_string())]).is_err());
+        assert!(with_text_chunks(vec![0; 40], &[("a\0b".to_string(), "text".to_string())]).is_err());
+    }
+
+}
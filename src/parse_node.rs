@@ -0,0 +1,195 @@
+//
+// Under MIT license
+//
+
+use id_tree::{Tree, TreeBuilder, Node, NodeId};
+use id_tree::InsertBehavior::{AsRoot, UnderNode};
+use std::error::Error;
+use std::io;
+use crate::generic_traits::generic_traits::{String2StructureBuilder, Structure2PlotBuilder};
+use crate::round_trip::normalize_bracketed;
+use crate::{String2Tree, Tree2String};
+
+/// A plain representation of a constituency tree, independent of id_tree, for walking the results of the library.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseNode {
+    /// A node without children, holding its label (a word or a bare label).
+    Leaf(String),
+    /// A node with its label and its children, from left to right.
+    Branch(String, Vec<ParseNode>)
+}
+
+impl ParseNode {
+
+    ///
+    /// A method that parses a bracketed constituency string into a ParseNode, by String2Tree. The string is validated
+    /// and its whitespace is fixed as in normalize_constituency. Spaces, brackets and backslashes in the labels are
+    /// escaped with a backslash, as to_bracketed writes them (see String2Tree::set_escapes). Returns an error if the
+    /// string is not a single valid tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use parsed_to_plot::ParseNode;
+    ///
+    /// let parse_node = ParseNode::from_bracketed("(S (NP (N Hello)))").unwrap();
+    /// assert_eq!(parse_node.to_bracketed(), "(S (NP (N (Hello))))");
+    /// ```
+    ///
+    pub fn from_bracketed(constituency: &str) -> Result<ParseNode, Box<dyn Error>> {
+
+        // a forest is normalized tree by tree, and rejected by the parser (no synthetic root is added)
+        let mut normalized = normalize_bracketed(constituency, true)?;
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_escapes(true);
+        string2tree.build(&mut normalized)?;
+        Ok(to_parse_node(&string2tree.get_structure()).ok_or("the tree is empty")?)
+    }
+
+    ///
+    /// A method that writes the ParseNode as a bracketed constituency string, by Tree2String. Every leaf is written
+    /// in brackets of its own, "(N (Hello))", as in normalize_constituency, which from_bracketed parses back.
+    /// Spaces, brackets and backslashes in the labels are escaped with a backslash, "(N (New\ York))".
+    ///
+    pub fn to_bracketed(&self) -> String {
+        let mut tree2string: Tree2String = Structure2PlotBuilder::new(self.to_tree());
+        tree2string.set_escapes(true);
+        tree2string.build_to_writer(&mut io::sink()).expect("a tree built from a ParseNode has a root");
+        tree2string.get_constituency(false)
+    }
+
+    ///
+    /// A method that converts the ParseNode back into a constituency tree, for the plotting and analysis methods
+    /// of the library.
+    ///
+    pub fn to_tree(&self) -> Tree<String> {
+        let mut tree: Tree<String> = TreeBuilder::new().build();
+        self.insert_subtree(&mut tree, None);
+        tree
+    }
+
+    // A helper method that inserts the node under the given parent (or as the root), and its children recursively.
+    fn insert_subtree(&self, tree: &mut Tree<String>, parent_id: Option<&NodeId>) {
+        let (label, children) = match self {
+            ParseNode::Leaf(label) => (label, &Vec::new()),
+            ParseNode::Branch(label, children) => (label, children)
+        };
+        let behavior = match parent_id {
+            Some(parent_id) => UnderNode(parent_id),
+            None => AsRoot
+        };
+        let node_id = tree.insert(Node::new(label.clone()), behavior).unwrap();
+        for child in children {
+            child.insert_subtree(tree, Some(&node_id));
+        }
+    }
+}
+
+// A helper that converts the subtree of the given node, recursively.
+fn subtree_parse_node(tree: &Tree<String>, node_id: &NodeId) -> ParseNode {
+
+    let node = tree.get(node_id).unwrap();
+    match node.children().is_empty() {
+        true => ParseNode::Leaf(node.data().clone()),
+        false => ParseNode::Branch(
+            node.data().clone(),
+            node.children().iter().map(|child_id| subtree_parse_node(tree, child_id)).collect::<Vec<ParseNode>>()
+        )
+    }
+}
+
+///
+/// A method that converts a constituency tree into a nested ParseNode, so walking the tree does not require id_tree.
+/// Returns None if the tree is empty (String2Tree always builds a root).
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::{to_parse_node, ParseNode};
+///
+/// let mut constituency = String::from("(S (NP (N Hello)) (a))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+///
+/// let hello = ParseNode::Branch("NP".to_string(), vec![ParseNode::Branch("N".to_string(), vec![ParseNode::Leaf("Hello".to_string())])]);
+/// assert_eq!(to_parse_node(&string2tree.get_structure()).unwrap(), ParseNode::Branch("S".to_string(), vec![hello, ParseNode::Leaf("a".to_string())]));
+/// ```
+///
+pub fn to_parse_node(tree: &Tree<String>) -> Option<ParseNode> {
+    let root_id = tree.root_node_id()?;
+    Some(subtree_parse_node(tree, root_id))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use id_tree::Tree;
+    use super::{to_parse_node, ParseNode};
+    use crate::{String2StructureBuilder, String2Tree, root_to_leaf_paths, normalize_constituency};
+
+    // A helper that collects the root to leaf paths of a ParseNode, as root_to_leaf_paths does for a tree.
+    fn paths(node: &ParseNode, stack: &mut Vec<String>, paths_out: &mut Vec<Vec<String>>) {
+        match node {
+            ParseNode::Leaf(label) => {
+                stack.push(label.clone());
+                paths_out.push(stack.clone());
+            },
+            ParseNode::Branch(label, children) => {
+                stack.push(label.clone());
+                children.iter().for_each(|child| paths(child, stack, paths_out));
+            }
+        }
+        stack.pop();
+    }
+
+    #[test]
+    fn parse_node() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let tree = string2tree.get_structure();
+
+        // the same nodes in the same order as the tree
+        let parse_node = to_parse_node(&tree).unwrap();
+        let mut parse_node_paths = Vec::new();
+        paths(&parse_node, &mut Vec::new(), &mut parse_node_paths);
+        assert_eq!(parse_node_paths, root_to_leaf_paths(&tree));
+
+        let mut constituency = String::from("(S)");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        assert_eq!(to_parse_node(&string2tree.get_structure()), Some(ParseNode::Leaf("S".to_string())));
+        assert_eq!(to_parse_node(&Tree::new()), None);
+    }
+
+    #[test]
+    fn bracketed() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let parse_node = ParseNode::from_bracketed(example).unwrap();
+        assert_eq!(parse_node.to_bracketed(), normalize_constituency(example).unwrap());
+        assert_eq!(ParseNode::from_bracketed(&parse_node.to_bracketed()).unwrap(), parse_node);
+
+        // the tree is rebuilt with the same nodes
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut example.to_string()).unwrap();
+        assert_eq!(root_to_leaf_paths(&parse_node.to_tree()), root_to_leaf_paths(&string2tree.get_structure()));
+
+        assert_eq!(ParseNode::Leaf("S".to_string()).to_bracketed(), "(S)");
+
+        // labels with spaces and brackets are escaped, and parsed back
+        let escaped = ParseNode::Branch("S".to_string(), vec![
+            ParseNode::Branch("-(-".to_string(), vec![ParseNode::Leaf("a b".to_string())]),
+            ParseNode::Branch("N".to_string(), vec![ParseNode::Leaf("c\\d)".to_string())])
+        ]);
+        assert_eq!(escaped.to_bracketed(), "(S (-\\(- (a\\ b)) (N (c\\\\d\\))))");
+        assert_eq!(ParseNode::from_bracketed(&escaped.to_bracketed()).unwrap(), escaped);
+        assert_eq!(ParseNode::from_bracketed(&ParseNode::Leaf("a b".to_string()).to_bracketed()).unwrap(), ParseNode::Leaf("a b".to_string()));
+        for invalid in ["(S (NP (N Hello))", "(S (NP a b))", "((S (a)))", "(S ())", "(S (a)))", "S", "", "(S (a)) (S (b))"] {
+            assert!(ParseNode::from_bracketed(invalid).is_err(), "{} should be invalid", invalid);
+        }
+    }
+
+}
@@ -0,0 +1,186 @@
+//
+// Under MIT license
+//
+
+use std::error::Error;
+use std::io;
+use crate::generic_traits::generic_traits::{String2StructureBuilder, Structure2PlotBuilder};
+use crate::string_2_tree::ESCAPE;
+use crate::{String2Tree, Tree2String};
+
+const BRACKETS: [char; 2] = ['(', ')'];
+
+///
+/// A method that normalizes the whitespace of a constituency or conll string, for comparisons that ignore formatting.
+/// Brackets are separated from their labels, and every run of whitespace (spaces, tabs, new lines) becomes a single space.
+///
+pub fn normalize_whitespace(text: &str) -> String {
+    text.chars()
+    .map(|c| match BRACKETS.contains(&c) {
+        true => format!(" {} ", c),
+        false => c.to_string()
+    })
+    .collect::<String>()
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ")
+}
+
+///
+/// A method that compares a reconstructed string to the original input, ignoring incidental whitespace.
+/// Two strings are equal if they have the same labels and brackets in the same order, see normalize_whitespace.
+/// For a conll, the lines can be joined with new lines before the comparison.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::Tree2String;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::Structure2PlotBuilder;
+/// use parsed_to_plot::equal_ignoring_whitespace;
+///
+/// let example = "(S  (NP (det The) (N people))  (VP (V watch) (NP (det the) (N game))))\n";
+/// let mut constituency = String::from(example);
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+///
+/// let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+/// tree2string.build("Output/constituency_normalized.txt").unwrap();
+/// assert!(equal_ignoring_whitespace(&tree2string.get_constituency(true), example));
+/// ```
+///
+pub fn equal_ignoring_whitespace(reconstructed: &str, original: &str) -> bool {
+    normalize_whitespace(reconstructed) == normalize_whitespace(original)
+}
+
+
+///
+/// A method that normalizes a constituency string by parsing it to a tree and reconstructing it, without the need to
+/// handle the tree (String2Tree and Tree2String are used internally). The canonical form has a single space between
+/// nodes and every leaf in brackets of its own, "(det (The))", whether it was given like that or as "(det The)".
+/// Whitespace around brackets is fixed before parsing, so "( S (NP(N dog)))" is accepted. A forest of several trees is
+/// normalized tree by tree, separated by a single space. Returns the error of the parser if the string is not a valid tree.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::normalize_constituency;
+///
+/// let normalized = normalize_constituency("( S  (NP (det The) (N people))(VP (V watch)))").unwrap();
+/// assert_eq!(normalized, "(S (NP (det (The)) (N (people))) (VP (V (watch))))");
+/// assert!(normalize_constituency("(S (NP (det The) (N people))").is_err());
+/// ```
+///
+pub fn normalize_constituency(constituency: &str) -> Result<String, Box<dyn Error>> {
+    normalize_bracketed(constituency, false)
+}
+
+// A helper that splits a constituency into its brackets and labels. When escapes is true (see String2Tree::set_escapes),
+// escaped brackets and whitespace belong to the labels and are kept escaped.
+fn constituency_tokens(constituency: &str, escapes: bool) -> Vec<String> {
+
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut escaped = false;
+    for c in constituency.chars() {
+        if escaped || (escapes && c == ESCAPE) {
+            escaped = !escaped;
+            token.push(c);
+        } else if c.is_whitespace() || BRACKETS.contains(&c) {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        } else {
+            token.push(c);
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+// A helper of normalize_constituency, that parses and writes back escaped labels when escapes is true.
+pub(in crate) fn normalize_bracketed(constituency: &str, escapes: bool) -> Result<String, Box<dyn Error>> {
+
+    // every bracket and label is a token, an open bracket is attached to the token after it and a close
+    // bracket to the token before it. Structures that the parser does not accept are reported by the parser.
+    let mut spaced = String::new();
+    let (mut depth, mut n_trees) = (0, 0);
+    let mut previous: Option<String> = None;
+    for token in constituency_tokens(constituency, escapes) {
+        match token.as_str() {
+            "(" => {
+                if !spaced.is_empty() && previous.as_deref() != Some("(") {
+                    spaced.push(' ');
+                }
+                spaced.push('(');
+                depth += 1;
+            },
+            ")" => {
+                spaced.push(')');
+                depth -= 1;
+                n_trees += (depth == 0) as usize;
+            },
+            label => {
+                if previous.as_deref() != Some("(") {
+                    spaced.push(' ');
+                }
+                spaced.push_str(label);
+            }
+        }
+        previous = Some(token);
+    }
+
+    // a forest is parsed under a synthetic root, and reconstructed without it
+    let mut string2tree: String2Tree = String2StructureBuilder::new();
+    string2tree.set_escapes(escapes);
+    string2tree.set_wrap_forest(n_trees > 1);
+    string2tree.build(&mut spaced)?;
+    let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+    tree2string.set_escapes(escapes);
+    if n_trees > 1 {
+        tree2string.set_forest_separator(Some(" "));
+    }
+    tree2string.build_to_writer(&mut io::sink())?;
+    Ok(tree2string.get_constituency(false))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{normalize_whitespace, equal_ignoring_whitespace, normalize_constituency};
+
+    #[test]
+    fn normalize() {
+        assert_eq!(normalize_whitespace(" (S  (NP (det The))\t(VP (V watch)) )\r\n"), "( S ( NP ( det The ) ) ( VP ( V watch ) ) )");
+        assert!(equal_ignoring_whitespace("(S (0 (1)))", "(S (0 (1) ) )"));
+        assert!(!equal_ignoring_whitespace("(S (0 (1)))", "(S (0 1))"));
+        assert!(equal_ignoring_whitespace("0\tThe\tthe\tDET\n1\tpeople\tpeople\tNOUN", "0 The the DET\n\n1 people people NOUN\n"));
+    }
+
+    #[test]
+    fn constituency() {
+        let canonical = "(S (NP (det (The)) (N (people))) (VP (V (watch))))";
+        for example in [
+            "(S (NP (det The) (N people)) (VP (V watch)))",
+            "(S (NP (det (The)) (N (people))) (VP (V (watch))))",
+            " ( S\t(NP (det The)(N people) )\n(VP (V watch)))\r\n"
+        ] {
+            assert_eq!(normalize_constituency(example).unwrap(), canonical);
+        }
+        assert_eq!(normalize_constituency(canonical).unwrap(), canonical);
+        assert_eq!(normalize_constituency("(S)").unwrap(), "(S)");
+        assert_eq!(normalize_constituency("(S a)(S (b c))").unwrap(), "(S (a)) (S (b (c)))");
+
+        for invalid in ["", "S", "(S (NP a b))", "((S (a)))", "(S ())", "(S (a)))", "(S (a)"] {
+            assert!(normalize_constituency(invalid).is_err(), "{} was normalized", invalid);
+        }
+    }
+
+}
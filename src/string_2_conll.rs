@@ -3,10 +3,21 @@
 // Under MIT license
 //
 
+use std::collections::HashMap;
 use std::error::Error;
+use std::io::BufRead;
 use crate::generic_traits::generic_traits::String2StructureBuilder;
+use crate::warnings::Warning;
 
 const CONLL_SIZE: usize = 10;
+const COMMENT_PREFIX: &str = "#";
+pub(in crate) const EMPTY_FIELD: &str = "_";
+const SENT_ID_PREFIX: &str = "# sent_id = ";
+const TEXT_PREFIX: &str = "# text = ";
+const COMPACT_SEPARATOR: char = '/';
+const COMPACT_SIZE: usize = 4;
+pub(in crate) const UNATTACHED_HEAD: f32 = -1.0;  // the head of a token that is not attached to any other token
+pub(in crate) const VIRTUAL_ROOT_HEAD: f32 = 0.0;  // the head of the root in UD, when ids start at 1 and 0 is not a token
 
 /// A struct that wraps the fields of a conll. The token struct and impl are not used by the user, rather The String2Conll implementation 
 #[derive(Clone, Debug)]
@@ -32,7 +43,7 @@ impl Token {
         return self.id
     }
     ///
-    /// A get method to retrive the token head of self
+    /// A get method to retrive the token head of self (-1 if the head field was empty, marking an unattached token)
     /// 
     pub fn get_token_head(&self) -> f32 {
         return self.head
@@ -86,26 +97,50 @@ impl Token {
         return self.misc.clone()
     }
     
-    fn new(input: Vec<String>) -> Token {
+    // A constructor from the fields that are used for plotting, the other fields are filled with "_".
+    // Used when the conll is given as parallel arrays rather than as strings.
+    pub(in crate) fn from_fields(id: f32, form: &str, pos: &str, head: f32, deprel: &str) -> Token {
+        Self {
+            id,
+            form: form.to_string(),
+            lemma: EMPTY_FIELD.to_string(),
+            pos: pos.to_string(),
+            xpos: EMPTY_FIELD.to_string(),
+            feats: EMPTY_FIELD.to_string(),
+            head,
+            deprel: deprel.to_string(),
+            deps: EMPTY_FIELD.to_string(),
+            misc: EMPTY_FIELD.to_string()
+        }
+    }
+
+    fn new(input: Vec<String>) -> Result<Token, Box<dyn Error>> {
 
-        assert!(input.len() == CONLL_SIZE, "input line does not satisfy Token requirments");
+        if input.len() != CONLL_SIZE {
+            return Err(format!("expected {} fields, found {}", CONLL_SIZE, input.len()).into());
+        }
         let mut iter = input.into_iter();
 
         // id (int), form, lemma, upos, xpos, feats, head, deprel, deps, misc
         // for the needs of plotting dependency only id, form, pos, head and deprel are used
-        let id = iter.next().unwrap().to_string().parse::<f32>().unwrap();
+        let id = iter.next().unwrap();
+        let id = id.parse::<f32>().map_err(|_| format!("the id {} is not a number", id))?;
         let form = iter.next().unwrap().to_string();
         let lemma = iter.next().unwrap().to_string();
         let pos = iter.next().unwrap().to_string();
         let xpos = iter.next().unwrap().to_string();
         let feats = iter.next().unwrap().to_string();
-        let head = iter.next().unwrap().to_string().parse::<f32>().unwrap();
+        // an empty head field marks an unattached token, as does an explicit -1
+        let head = match iter.next().unwrap() {
+            x if x == EMPTY_FIELD => UNATTACHED_HEAD,
+            x => x.parse::<f32>().map_err(|_| format!("the head {} is not a number", x))?
+        };
         let deprel = iter.next().unwrap().to_string();
         let deps = iter.next().unwrap().to_string();
         let misc = iter.next().unwrap().to_string();
         assert!(iter.next().is_none());
 
-        Self {
+        Ok(Self {
             id: id,
             form: form,
             lemma: lemma,
@@ -116,16 +151,329 @@ impl Token {
             deprel: deprel,
             deps: deps,
             misc: misc
+        })
+    }
+
+    // A helper that names the separator that splits a line into the conll fields, if any, for error messages.
+    // Lines are expected to be tab separated, but are often copied with spaces instead.
+    fn detect_separator(line: &str) -> Option<&'static str> {
+        if line.split('\t').count() == CONLL_SIZE {
+            Some("tabs")
+        } else if line.split(' ').count() == CONLL_SIZE {
+            Some("single spaces")
+        } else if line.split_whitespace().count() == CONLL_SIZE {
+            Some("mixed whitespace")
+        } else {
+            None
         }
     }
 
 }
 
+/// A struct that wraps the tokens of a sentence together with optional sentence-level metadata,
+/// as given in the "# sent_id = " and "# text = " comments of a conll. Can be made from a Vec-Token-,
+/// and can be used to initialize Conll2Plot and Conll2String (using from).
+#[derive(Clone, Debug, Default)]
+pub struct Sentence {
+    tokens: Vec<Token>,
+    sent_id: Option<String>,
+    text: Option<String>
+}
+
+impl Sentence {
+
+    ///
+    /// A get method to retrive the tokens of the sentence
+    /// 
+    pub fn get_tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+    ///
+    /// A get method to retrive the sent_id of the sentence (if given)
+    /// 
+    pub fn get_sent_id(&self) -> Option<String> {
+        self.sent_id.clone()
+    }
+    ///
+    /// A get method to retrive the text of the sentence (if given)
+    /// 
+    pub fn get_text(&self) -> Option<String> {
+        self.text.clone()
+    }
+    ///
+    /// A set method to determine the sent_id of the sentence
+    /// 
+    pub fn set_sent_id(&mut self, sent_id: &str) {
+        self.sent_id = Some(sent_id.to_string());
+    }
+    ///
+    /// A set method to determine the text of the sentence
+    /// 
+    pub fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_string());
+    }
+    ///
+    /// A get method to retrive the forms of the tokens in the sentence
+    /// 
+    pub fn get_forms(&self) -> Vec<String> {
+        self.tokens.iter().map(|token| token.get_token_form()).collect::<Vec<String>>()
+    }
+    ///
+    /// The number of tokens in the sentence
+    /// 
+    pub fn len(&self) -> usize {
+        self.tokens.len()
+    }
+    ///
+    /// Whether the sentence has no tokens
+    /// 
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    // The metadata of the sentence as conll comment lines, used in reconstruction.
+    pub(in crate) fn get_comments(&self) -> Vec<String> {
+        let mut comments = Vec::new();
+        if let Some(sent_id) = &self.sent_id {
+            comments.push(format!("{}{}", SENT_ID_PREFIX, sent_id));
+        }
+        if let Some(text) = &self.text {
+            comments.push(format!("{}{}", TEXT_PREFIX, text));
+        }
+        comments
+    }
+}
+
+impl From<Vec<Token>> for Sentence {
+    fn from(tokens: Vec<Token>) -> Self {
+        Self {
+            tokens,
+            sent_id: None,
+            text: None
+        }
+    }
+}
+
+impl From<Sentence> for Vec<Token> {
+    fn from(sentence: Sentence) -> Self {
+        sentence.tokens
+    }
+}
+
 /// A String2StructureBuilder sturct, mainly holds the tokens object. This type will implement the String2StructureBuilder,
 /// with a dependency vec string as Input and a made Vec-Token- as output.
 #[derive(Clone)]
 pub struct String2Conll {
-    tokens: Vec<Token>
+    tokens: Vec<Token>,
+    warnings: Vec<Warning>,
+    sent_id: Option<String>,
+    text: Option<String>,
+    one_based: bool
+}
+
+impl String2Conll {
+
+    ///
+    /// A set method to determine whether the ids are 1-based as in UD (default false). If true, the ids and heads
+    /// are shifted to be 0-based while building, and a head of 0 (the virtual root) makes the token the root,
+    /// i.e its own head. Should be called before build.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "1\tpeople\tpeople\tNOUN\t_\t_\t2\tnsubj\t_\t_",
+    ///     "2\twatch\twatch\tVERB\t_\t_\t0\troot\t_\t_"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.set_one_based(true);
+    /// string2conll.build(&mut dependency).unwrap();
+    /// 
+    /// let conll = string2conll.get_structure();
+    /// let prediction = conll.iter().map(|x| (x.get_token_id(), x.get_token_head())).collect::<Vec<(f32, f32)>>();
+    /// assert_eq!(prediction, vec![(0.0, 1.0), (1.0, 1.0)]);
+    /// ```
+    /// 
+    pub fn set_one_based(&mut self, one_based: bool) {
+        self.one_based = one_based;
+    }
+
+    ///
+    /// A method that clears the built tokens, warnings and metadata, so the same builder can be reused for another
+    /// sentence without reallocating the tokens. build must be called on a reset builder before get_structure.
+    /// 
+    pub fn reset(&mut self) {
+        self.tokens.clear();
+        self.warnings.clear();
+        self.sent_id = None;
+        self.text = None;
+    }
+
+    ///
+    /// A method that reads the lines of a single sentence from a reader, up to a blank line or the end of the input,
+    /// and builds the String2Conll from them (leading blank lines are skipped). Returns None if the reader
+    /// has no more sentences, so calling it repeatedly streams a file sentence by sentence.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let input = "0\tHello\thello\tINTJ\t_\t_\t0\tROOT\t_\t_\n\n0\tBye\tbye\tINTJ\t_\t_\t0\tROOT\t_\t_\n";
+    /// let mut reader = std::io::BufReader::new(input.as_bytes());
+    /// 
+    /// let mut forms = Vec::new();
+    /// while let Some(string2conll) = String2Conll::from_reader(&mut reader).unwrap() {
+    ///     forms.push(string2conll.get_structure()[0].get_token_form());
+    /// }
+    /// assert_eq!(forms, vec!["Hello", "Bye"]);
+    /// ```
+    /// 
+    pub fn from_reader<R: BufRead>(reader: &mut R) -> Result<Option<Self>, Box<dyn Error>> {
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let trimmed = line.trim_end_matches(['\n', '\r']);
+            if !trimmed.trim().is_empty() {
+                lines.push(trimmed.to_string());
+            } else if !lines.is_empty() {
+                break;
+            }
+            line.clear();
+        }
+
+        if lines.is_empty() {
+            return Ok(None);
+        }
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut lines)?;
+        Ok(Some(string2conll))
+    }
+
+    ///
+    /// A method that builds the String2Conll from a compact single line notation, easier to write by hand than a conll.
+    /// Every token is given as form/pos/head/deprel, and the tokens are separated by whitespace. The tokens are
+    /// numbered by their order from 0 (or from 1, see set_one_based), the head is the number of the head token
+    /// and the root is its own head. When no token is its own head, a head of 0 marks the root instead, as in UD
+    /// (otherwise the heads would form a cycle). A head of "_" marks an unattached token, as in a conll. The form may
+    /// contain "/" (the fields are split from the right), the other fields may not.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build_compact("The/DET/1/det people/NOUN/2/nsubj watch/VERB/2/ROOT").unwrap();
+    /// 
+    /// let conll = string2conll.get_structure();
+    /// let prediction = conll.iter().map(|x| (x.get_token_form(), x.get_token_head())).collect::<Vec<(String, f32)>>();
+    /// assert_eq!(prediction, vec![("The".to_string(), 1.0), ("people".to_string(), 2.0), ("watch".to_string(), 2.0)]);
+    /// ```
+    /// 
+    pub fn build_compact(&mut self, input: &str) -> Result<(), Box<dyn Error>> {
+
+        // every token is translated to a conll line, with empty fields for the fields that are not given
+        let first_id = if self.one_based { 1 } else { 0 };
+        let mut tokens: Vec<(String, &str, &str, String, &str)> = Vec::new();
+        for (i, compact) in input.split_whitespace().enumerate() {
+            let fields = compact.rsplitn(COMPACT_SIZE, COMPACT_SEPARATOR).collect::<Vec<&str>>();
+            if fields.len() != COMPACT_SIZE || fields.iter().any(|field| field.is_empty()) {
+                return Err(format!("token {}: expected form/pos/head/deprel, found {}", i, compact).into());
+            }
+            tokens.push(((first_id + i).to_string(), fields[3], fields[2], fields[1].to_string(), fields[0]));
+        }
+
+        // without a token that is its own head, a head of 0 is the root (a 1-based build replaces it by itself)
+        let virtual_root = VIRTUAL_ROOT_HEAD.to_string();
+        if !self.one_based && tokens.iter().all(|(id, _, _, head, _)| id != head) {
+            for (id, _, _, head, _) in tokens.iter_mut().filter(|(_, _, _, head, _)| *head == virtual_root) {
+                *head = id.clone();
+            }
+        }
+
+        let mut lines = tokens.iter()
+        .map(|(id, form, pos, head, deprel)| [id.as_str(), form, EMPTY_FIELD, pos, EMPTY_FIELD, EMPTY_FIELD, head.as_str(), deprel, EMPTY_FIELD, EMPTY_FIELD].join("\t"))
+        .collect::<Vec<String>>();
+
+        if lines.is_empty() {
+            return Err("no tokens found in the compact input".into());
+        }
+        self.build(&mut lines)
+    }
+
+    ///
+    /// A get method to retrieve the warnings accumulated during build (for example, skipped comment lines).
+    /// 
+    pub fn get_warnings(&self) -> Vec<Warning> {
+        self.warnings.clone()
+    }
+
+    ///
+    /// Get a copy of the conll as a Sentence, including the sent_id and text metadata if they
+    /// were given as comments in the input (should be called after build)
+    /// 
+    pub fn get_sentence(&self) -> Sentence {
+        let mut sentence = Sentence::from(self.get_structure());
+        sentence.sent_id = self.sent_id.clone();
+        sentence.text = self.text.clone();
+        sentence
+    }
+
+    ///
+    /// A method that sorts the built tokens by their ids, in case the input lines were not in id order.
+    /// If renumber is true, the ids are also renumbered to be contiguous from 0, and the heads are remapped
+    /// accordingly. A head that does not match any token id could collide with the new ids, so it becomes unattached,
+    /// except for the virtual root 0 (as in UD), which is replaced by the token itself. Should be called after build.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Conll;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut dependency = [
+    ///     "4	game	game	NOUN	_	_	2	dobj	_	_",
+    ///     "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+    ///     "1	people	people	NOUN	_	_	2	nsubj	_	_"
+    /// ].map(|x| x.to_string()).to_vec();
+    /// 
+    /// let mut string2conll: String2Conll = String2StructureBuilder::new();
+    /// string2conll.build(&mut dependency).unwrap();
+    /// string2conll.sort_by_id(true);
+    /// 
+    /// let conll = string2conll.get_structure();
+    /// assert_eq!(conll.first().unwrap().get_token_form(), "people");
+    /// assert_eq!(conll.last().unwrap().get_token_id(), 2.0);
+    /// assert_eq!(conll.last().unwrap().get_token_head(), 1.0);
+    /// ```
+    /// 
+    pub fn sort_by_id(&mut self, renumber: bool) {
+
+        self.tokens.sort_by(|x, y| x.id.partial_cmp(&y.id).expect("token ids should be comparable"));
+
+        if renumber {
+            let old2new = self.tokens.iter().enumerate()
+            .map(|(i, token)| (token.id.to_bits(), i as f32))
+            .collect::<HashMap<u32, f32>>();
+            for token in self.tokens.iter_mut() {
+                token.id = old2new[&token.id.to_bits()];
+                token.head = match (old2new.get(&token.head.to_bits()), token.head) {
+                    (Some(head), _) => *head,
+                    (None, VIRTUAL_ROOT_HEAD) => token.id,
+                    (None, _) => UNATTACHED_HEAD
+                };
+            }
+        }
+    }
 }
 
 impl String2StructureBuilder for String2Conll {
@@ -148,7 +496,11 @@ impl String2StructureBuilder for String2Conll {
     fn new() -> Self {
         
         Self {
-            tokens: Vec::new()
+            tokens: Vec::new(),
+            warnings: Vec::new(),
+            sent_id: None,
+            text: None,
+            one_based: false
         }
     }
 
@@ -198,10 +550,39 @@ impl String2StructureBuilder for String2Conll {
     fn build(&mut self, input: &mut Self::Input) -> Result<(), Box<dyn Error>> {
 
         // the input is a vector of strings, each string is a line in conll (token string represenation)
-        for line in input.iter() {
+        for (i, line) in input.iter().enumerate() {
+
+            // comment lines (like "# sent_id = 1") are not tokens, skip them (keep sentence metadata)
+            if line.starts_with(COMMENT_PREFIX) {
+                if let Some(sent_id) = line.strip_prefix(SENT_ID_PREFIX) {
+                    self.sent_id = Some(sent_id.to_string());
+                } else if let Some(text) = line.strip_prefix(TEXT_PREFIX) {
+                    self.text = Some(text.to_string());
+                }
+                self.warnings.push(Warning::LineSkipped(i, "comment line".to_string()));
+                continue;
+            }
     
+            // a line that does not split to the conll fields by tabs is reported with the separator that does split it
             let token_vec: Vec<String> = line.split("\t").map(|s| s.to_string()).collect();
-            let token = Token::new(token_vec);
+            if token_vec.len() != CONLL_SIZE {
+                let detected = match Token::detect_separator(line) {
+                    Some(separator) => format!("the fields seem to be separated by {}", separator),
+                    None => "no separator splits the line to the expected fields".to_string()
+                };
+                return Err(format!("line {}: expected {} tab separated fields, found {} ({})", i, CONLL_SIZE, token_vec.len(), detected).into());
+            }
+            let mut token = Token::new(token_vec).map_err(|e| format!("line {}: {}", i, e))?;
+
+            // 1-based ids are shifted to the 0-based indexing, the virtual root 0 is replaced by the token itself
+            if self.one_based {
+                token.id -= 1.0;
+                token.head = match token.head {
+                    VIRTUAL_ROOT_HEAD => token.id,
+                    UNATTACHED_HEAD => UNATTACHED_HEAD,
+                    head => head - 1.0
+                };
+            }
             self.tokens.push(token);
         }
 
@@ -213,7 +594,8 @@ impl String2StructureBuilder for String2Conll {
 #[cfg(test)]
 mod tests {
 
-    use super::String2Conll;
+    use super::{String2Conll, UNATTACHED_HEAD};
+    use crate::warnings::Warning;
     use crate::generic_traits::generic_traits::String2StructureBuilder;
 
     #[test]
@@ -239,4 +621,148 @@ mod tests {
         let prediction_last_token_id = conll.last().unwrap().get_token_id();
         assert_eq!(prediction_last_token_id, gold_last_token_id);
     }
+
+    #[test]
+    fn sort_reversed() {
+
+        let mut dependency = [
+            "4	game	game	NOUN	_	_	2	dobj	_	_",
+            "3	the	the	DET	_	_	4	det	_	_",
+            "2	watch	watch	VERB	_	_	2	ROOT	_	_",
+            "1	people	people	NOUN	_	_	2	nsubj	_	_",
+            "0	The	the	DET	_	_	1	det	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        string2conll.sort_by_id(false);
+        let conll = string2conll.get_structure();
+
+        let prediction = conll.iter().map(|x| x.get_token_form()).collect::<Vec<String>>();
+        assert_eq!(prediction, vec!["The", "people", "watch", "the", "game"]);
+    }
+
+    #[test]
+    fn sort_renumber() {
+
+        let mut dependency = [
+            "7	game	game	NOUN	_	_	5	dobj	_	_",
+            "5	watch	watch	VERB	_	_	5	ROOT	_	_",
+            "3	people	people	NOUN	_	_	5	nsubj	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        string2conll.sort_by_id(true);
+        let conll = string2conll.get_structure();
+
+        let prediction = conll.iter().map(|x| (x.get_token_id(), x.get_token_head())).collect::<Vec<(f32, f32)>>();
+        assert_eq!(prediction, vec![(0.0, 1.0), (1.0, 1.0), (2.0, 1.0)]);
+
+        // a head out of range (2 is not a token) would collide with the new id 2, a virtual root 0 is the root
+        let mut dependency = [
+            "3	people	people	NOUN	_	_	2	nsubj	_	_",
+            "5	watch	watch	VERB	_	_	0	ROOT	_	_",
+            "7	game	game	NOUN	_	_	5	dobj	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        string2conll.sort_by_id(true);
+        let prediction = string2conll.get_structure().iter().map(|x| (x.get_token_id(), x.get_token_head())).collect::<Vec<(f32, f32)>>();
+        assert_eq!(prediction, vec![(0.0, UNATTACHED_HEAD), (1.0, 1.0), (2.0, 1.0)]);
+    }
+
+    #[test]
+    fn skip_comments() {
+
+        let mut dependency = [
+            "# sent_id = 1",
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	1	ROOT	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let conll = string2conll.get_structure();
+
+        assert_eq!(conll.len(), 2);
+        assert_eq!(string2conll.get_warnings(), vec![Warning::LineSkipped(0, "comment line".to_string())]);
+    }
+
+    #[test]
+    fn sentence_metadata() {
+
+        let mut dependency = [
+            "# sent_id = 1",
+            "# text = The people",
+            "0	The	the	DET	_	_	1	det	_	_",
+            "1	people	people	NOUN	_	_	1	ROOT	_	_"
+        ].map(|x| x.to_string()).to_vec();
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut dependency).unwrap();
+        let sentence = string2conll.get_sentence();
+
+        assert_eq!(sentence.get_sent_id(), Some("1".to_string()));
+        assert_eq!(sentence.get_text(), Some("The people".to_string()));
+        assert_eq!(sentence.get_forms(), vec!["The", "people"]);
+        assert_eq!(sentence.get_comments(), dependency[..2].to_vec());
+    }
+
+    #[test]
+    fn reset() {
+
+        let mut string2conll: String2Conll = String2StructureBuilder::new();
+        string2conll.build(&mut vec!["# sent_id = 1".to_string(), "0	The	the	DET	_	_	0	ROOT	_	_".to_string()]).unwrap();
+        string2conll.reset();
+        string2conll.build(&mut vec!["0	people	people	NOUN	_	_	0	ROOT	_	_".to_string()]).unwrap();
+
+        let sentence = string2conll.get_sentence();
+        assert_eq!(sentence.get_forms(), vec!["people"]);
+        assert_eq!(sentence.get_sent_id(), None);
+        assert!(string2conll.get_warnings().is_empty());
+    }
+
+    #[test]
+    fn separator_errors() {
+
+        let build = |line: &str| {
+            let mut dependency = vec!["0	The	the	DET	_	_	1	det	_	_".to_string(), line.to_string()];
+            let mut string2conll: String2Conll = String2StructureBuilder::new();
+            string2conll.build(&mut dependency).unwrap_err().to_string()
+        };
+
+        assert_eq!(build("1 people people NOUN _ _ 1 ROOT _ _"), "line 1: expected 10 tab separated fields, found 1 (the fields seem to be separated by single spaces)");
+        assert_eq!(build("1\tpeople people NOUN _ _ 1 ROOT _ _"), "line 1: expected 10 tab separated fields, found 2 (the fields seem to be separated by mixed whitespace)");
+        assert_eq!(build("1	people	NOUN	1	ROOT"), "line 1: expected 10 tab separated fields, found 5 (no separator splits the line to the expected fields)");
+        assert_eq!(build("x	people	people	NOUN	_	_	1	ROOT	_	_"), "line 1: the id x is not a number");
+    }
+
+    #[test]
+    fn compact() {
+        let tokens = |input: &str, one_based: bool| {
+            let mut string2conll: String2Conll = String2StructureBuilder::new();
+            string2conll.set_one_based(one_based);
+            string2conll.build_compact(input).map(|_| string2conll.get_structure()
+            .iter().map(|x| (x.get_token_id(), x.get_token_form(), x.get_token_pos(), x.get_token_head(), x.get_token_deprel())).collect::<Vec<_>>())
+        };
+
+        let golden = vec![
+            (0.0, "1/2".to_string(), "NUM".to_string(), 1.0, "nummod".to_string()),
+            (1.0, "cup".to_string(), "NOUN".to_string(), 1.0, "ROOT".to_string()),
+            (2.0, "!".to_string(), "PUNCT".to_string(), -1.0, "_".to_string())
+        ];
+        assert_eq!(tokens("1/2/NUM/1/nummod  cup/NOUN/1/ROOT\n!/PUNCT/_/_", false).unwrap(), golden);
+        assert_eq!(tokens("1/2/NUM/2/nummod cup/NOUN/0/ROOT !/PUNCT/_/_", true).unwrap()[..2], golden[..2]);
+
+        // without a token that is its own head, head 0 is the root rather than the first token
+        let heads = |input: &str| tokens(input, false).unwrap().iter().map(|x| x.3).collect::<Vec<f32>>();
+        assert_eq!(heads("The/DET/1/det people/NOUN/2/nsubj watch/VERB/0/ROOT"), vec![1.0, 2.0, 2.0]);
+        assert_eq!(heads("Watch/VERB/0/ROOT the/DET/2/det game/NOUN/0/obj"), vec![0.0, 2.0, 0.0]);
+
+        assert_eq!(tokens("The/DET/1 people/NOUN/1/ROOT", false).unwrap_err().to_string(), "token 0: expected form/pos/head/deprel, found The/DET/1");
+        assert_eq!(tokens("The/DET//det", false).unwrap_err().to_string(), "token 0: expected form/pos/head/deprel, found The/DET//det");
+        assert!(tokens(" ", false).is_err());
+    }
 }
\ No newline at end of file
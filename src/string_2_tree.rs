@@ -9,20 +9,184 @@ use id_tree::InsertBehavior::*;
 use id_tree::{Tree, NodeId};
 use crate::generic_traits::generic_traits::String2StructureBuilder;
 
-const NODE_DELIMITER: &str = " ";
+const NODE_DELIMITER: char = ' ';
 const CLOSE_BRACKETS: char = ')';
 const OPEN_BRACKETS: char = '(';
+pub(in crate) const ESCAPE: char = '\\';
+const ESCAPED_CHARS: [char; 4] = [NODE_DELIMITER, OPEN_BRACKETS, CLOSE_BRACKETS, ESCAPE];
+const INDEX_DELIMITER: char = '_';
+pub(in crate) const FOREST_ROOT: &str = "ROOT";
+
+/// An enum of the leaf conventions of a constituency string. In double leaves a leaf is written after its
+/// parent label without brackets, "(N game)", in singular leaves every leaf is wrapped in brackets, "(N (game))".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LeafStyle {
+    Double,
+    Singular,
+    Mixed
+}
 
 /// A String2Tree struct, mainly holds the tree object. This type will implement the String2StructureBuilder, 
 /// with a constituency String as Input and a made Tree-String- as output.
 pub struct String2Tree {
     tree: Tree<String>,
     parent_node_id: Option<NodeId>,
-    level_balance: i32
+    level_balance: i32,
+    strip_indices: bool,
+    leaf_indices: Vec<Option<usize>>,
+    n_double_leaves: usize,
+    n_singular_leaves: usize,
+    wrap_forest: bool,
+    escapes: bool
 }
 
 impl String2Tree {
 
+    ///
+    /// A set method to determine whether word indices attached to leaves (like "dog_3") are stripped (default false).
+    /// When set to true, the label of the leaf becomes "dog" and the index 3 is recorded, see get_leaf_indices.
+    /// Should be called before build.
+    /// 
+    pub fn set_strip_indices(&mut self, strip_indices: bool) {
+        self.strip_indices = strip_indices;
+    }
+
+    ///
+    /// A set method to determine whether a forest, i.e a string of several top level trees like "(NP ...) (VP ...)",
+    /// is wrapped under a synthetic ROOT node (default false). Without it, build returns an error for such strings.
+    /// A string of a single tree is not affected. Should be called before build.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut constituency = String::from("(NP (det The) (N people)) (VP (V watch))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.set_wrap_forest(true);
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let tree = string2tree.get_structure();
+    /// assert_eq!(tree.get(tree.root_node_id().unwrap()).unwrap().data(), "ROOT");
+    /// ```
+    /// 
+    pub fn set_wrap_forest(&mut self, wrap_forest: bool) {
+        self.wrap_forest = wrap_forest;
+    }
+
+    ///
+    /// A set method to determine whether a backslash escapes the next char of a label (default false), so labels
+    /// can hold spaces, brackets and backslashes, like "(NP (N New\ York))" or "(X (\())". The escapes are removed
+    /// from the labels of the tree, and Tree2String::set_escapes writes them back. Should be called before build.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut constituency = String::from("(NP (N New\\ York) (PUNCT \\)))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.set_escapes(true);
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let tree = string2tree.get_structure();
+    /// let labels = tree.traverse_pre_order(tree.root_node_id().unwrap()).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+    /// assert_eq!(labels, vec!["NP", "N", "New York", "PUNCT", ")"]);
+    /// ```
+    /// 
+    pub fn set_escapes(&mut self, escapes: bool) {
+        self.escapes = escapes;
+    }
+
+    ///
+    /// A method that clears the built tree and the parsing state, so the same builder can be reused for another
+    /// sentence without reallocating (the nodes of the tree are recycled). The settings are kept.
+    /// build must be called on a reset builder before get_structure, and node ids of the previous tree are invalid.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// 
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// for constituency in ["(S (NP (N People)) (VP (V watch)))", "(S (NP (N Time)) (VP (V flies)))"] {
+    ///     string2tree.reset();
+    ///     string2tree.build(&mut constituency.to_string()).unwrap();
+    ///     assert_eq!(string2tree.get_structure().height(), 4);
+    /// }
+    /// ```
+    /// 
+    pub fn reset(&mut self) {
+        if let Some(root_id) = self.tree.root_node_id().cloned() {
+            self.tree.remove_node(root_id, RemoveBehavior::DropChildren).expect("the root belongs to the tree");
+        }
+        self.parent_node_id = None;
+        self.level_balance = 0;
+        self.leaf_indices.clear();
+        self.n_double_leaves = 0;
+        self.n_singular_leaves = 0;
+    }
+
+    ///
+    /// A get method to retrieve the word indices that were stripped from the leaves during build.
+    /// The vector is ordered by the leaves from left to right, a leaf without an index has None.
+    /// Empty unless set_strip_indices(true) was called before build.
+    /// 
+    pub fn get_leaf_indices(&self) -> Vec<Option<usize>> {
+        self.leaf_indices.clone()
+    }
+
+    ///
+    /// A get method to retrieve the leaf convention of the constituency string, determined during build.
+    /// Mixed is returned when the string has leaves of both conventions. For double leaves, the original
+    /// string can be reconstructed using Tree2String::get_constituency with the inverse flag.
+    /// 
+    pub fn get_leaf_style(&self) -> LeafStyle {
+        match (self.n_double_leaves, self.n_singular_leaves) {
+            (_, 0) => LeafStyle::Double,
+            (0, _) => LeafStyle::Singular,
+            _ => LeafStyle::Mixed
+        }
+    }
+
+    // A method that splits an attached word index from a leaf, "dog_3" -> ("dog", Some(3)).
+    // Leaves without a numeric suffix are returned as is. Not exposed.
+    fn split_index(node_str: &str) -> (&str, Option<usize>) {
+        match node_str.rsplit_once(INDEX_DELIMITER) {
+            Some((label, index)) if !label.is_empty() => match index.parse::<usize>() {
+                Ok(index) => (label, Some(index)),
+                Err(_) => (node_str, None)
+            },
+            _ => (node_str, None)
+        }
+    }
+
+    // A method that counts the top level trees of a constituency string, by the times the brackets are balanced.
+    // Escaped brackets are not counted when escapes are used. Not exposed.
+    fn count_top_level(input: &str, escapes: bool) -> usize {
+        let mut depth: i32 = 0;
+        let mut n_top_level = 0;
+        for (i, c) in input.char_indices() {
+            if is_escaped(input, i, escapes) {
+                continue;
+            }
+            match c {
+                OPEN_BRACKETS => depth += 1,
+                CLOSE_BRACKETS => {
+                    depth -= 1;
+                    if depth == 0 {
+                        n_top_level += 1;
+                    }
+                },
+                _ => {}
+            }
+        }
+        n_top_level
+    }
+
     // A method that updates the current parent node in the parsing process.
     // This method isn't called directly as users, not exposed.
     fn update_parent(&mut self, item_id: &NodeId, closers: usize) -> Result<(), Box<dyn Error>> {
@@ -31,7 +195,7 @@ impl String2Tree {
             let ancestors_ids = self.tree.ancestor_ids(item_id)?.collect::<Vec<&NodeId>>();
             let parent_node_id = ancestors_ids
             .get(closers-1)
-            .expect("inconsistent number of closers and ancestors for node id")
+            .ok_or("inconsistent number of closers and ancestors for node id")?
             .to_owned()
             .to_owned();        
             self.parent_node_id = Some(parent_node_id);
@@ -45,6 +209,166 @@ impl String2Tree {
 
 }
 
+// A helper that checks whether the char at the byte index is escaped, i.e preceded by an odd number of escapes.
+// Nothing is escaped when escapes are not used.
+fn is_escaped(input: &str, index: usize, escapes: bool) -> bool {
+    escapes && input[..index].chars().rev().take_while(|c| *c == ESCAPE).count() % 2 == 1
+}
+
+// A helper that returns the byte indices of the unescaped occurrences of a char.
+fn unescaped_indices(input: &str, c: char, escapes: bool) -> Vec<usize> {
+    input.char_indices().filter(|(i, x)| *x == c && !is_escaped(input, *i, escapes)).map(|(i, _)| i).collect()
+}
+
+// A helper that counts the unescaped occurrences of a char.
+fn count_unescaped(input: &str, c: char, escapes: bool) -> usize {
+    unescaped_indices(input, c, escapes).len()
+}
+
+// A helper that splits a string once, on the first unescaped occurrence of a char (which is dropped).
+pub(in crate) fn split_once_unescaped(input: &str, c: char, escapes: bool) -> Option<(&str, &str)> {
+    unescaped_indices(input, c, escapes).first().map(|i| (&input[..*i], &input[*i + c.len_utf8()..]))
+}
+
+// A helper that splits a string on the unescaped occurrences of a char.
+pub(in crate) fn split_unescaped(input: &str, c: char, escapes: bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in unescaped_indices(input, c, escapes) {
+        parts.push(&input[start..i]);
+        start = i + c.len_utf8();
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+// A helper that strips the unescaped brackets around a node, "(A" -> "A", "(A)" -> "A", "A\))" -> "A\)".
+fn strip_brackets(node: &str, escapes: bool) -> &str {
+    let brackets = [OPEN_BRACKETS, CLOSE_BRACKETS];
+    let node = node.trim_start_matches(brackets);
+    let mut end = node.len();
+    while node[..end].ends_with(brackets) && !is_escaped(node, end - 1, escapes) {
+        end -= 1;
+    }
+    &node[..end]
+}
+
+// A helper that removes the escapes from a label, "New\ York" -> "New York".
+fn unescape_label(label: &str, escapes: bool) -> String {
+    if !escapes {
+        return label.to_string();
+    }
+    let mut unescaped = String::new();
+    let mut escaped = false;
+    for c in label.chars() {
+        if c == ESCAPE && !escaped {
+            escaped = true;
+            continue;
+        }
+        unescaped.push(c);
+        escaped = false;
+    }
+    unescaped
+}
+
+// A helper that escapes the chars of a label that the parser would split on, "New York" -> "New\ York".
+pub(in crate) fn escape_label(label: &str) -> String {
+    let mut escaped = String::new();
+    for c in label.chars() {
+        if ESCAPED_CHARS.contains(&c) {
+            escaped.push(ESCAPE);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+///
+/// A method that checks whether the brackets of a constituency string are balanced, without building the tree.
+/// A cheap precondition of String2Tree::build (for example, to give feedback while an input is typed),
+/// a balanced string can still fail to build for other reasons. See first_imbalance for the position of the problem,
+/// and is_balanced_escaped for strings with escaped brackets.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::is_balanced;
+///
+/// assert!(is_balanced("(S (NP (det The) (N people)) (VP (V watch)))"));
+/// assert!(!is_balanced("(S (NP (det The) (N people)) (VP (V watch))"));
+/// ```
+///
+pub fn is_balanced(input: &str) -> bool {
+    first_imbalance(input).is_none()
+}
+
+///
+/// A method that returns the byte position of the first imbalanced bracket of a constituency string, if any:
+/// either a closer without an opener before it, or otherwise the first opener that is never closed.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::first_imbalance;
+///
+/// assert_eq!(first_imbalance("(S (0)) 1)"), Some(9));
+/// assert_eq!(first_imbalance("(S (0) (1"), Some(0));
+/// assert_eq!(first_imbalance("(S (0 (1)))"), None);
+/// ```
+///
+pub fn first_imbalance(input: &str) -> Option<usize> {
+    imbalance(input, false)
+}
+
+///
+/// The same as is_balanced, for strings that are built with String2Tree::set_escapes(true):
+/// escaped brackets belong to labels and are not counted.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::is_balanced_escaped;
+///
+/// assert!(is_balanced_escaped("(S (PUNCT \\)))"));
+/// assert!(!is_balanced_escaped("(S (PUNCT \\))"));
+/// ```
+///
+pub fn is_balanced_escaped(input: &str) -> bool {
+    first_imbalance_escaped(input).is_none()
+}
+
+///
+/// The same as first_imbalance, for strings that are built with String2Tree::set_escapes(true):
+/// escaped brackets are skipped.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::first_imbalance_escaped;
+///
+/// assert_eq!(first_imbalance_escaped("(S (PUNCT \\())"), None);
+/// assert_eq!(first_imbalance_escaped("(S (X \\\\)))"), Some(10));
+/// ```
+///
+pub fn first_imbalance_escaped(input: &str) -> Option<usize> {
+    imbalance(input, true)
+}
+
+// A helper of first_imbalance and first_imbalance_escaped, escaped brackets are skipped when escapes is true.
+fn imbalance(input: &str, escapes: bool) -> Option<usize> {
+
+    // the positions of the openers that are not closed yet, a closer closes (pops) the last one
+    let mut openers: Vec<usize> = Vec::new();
+    for (position, c) in input.char_indices().filter(|(position, _)| !is_escaped(input, *position, escapes)) {
+        match c {
+            OPEN_BRACKETS => openers.push(position),
+            CLOSE_BRACKETS if openers.pop().is_none() => return Some(position),
+            _ => {}
+        }
+    }
+    openers.first().copied()
+}
+
 impl String2StructureBuilder for String2Tree {
 
     type Input = String;
@@ -67,6 +391,12 @@ impl String2StructureBuilder for String2Tree {
             tree: Tree::new(),
             parent_node_id: None,
             level_balance: 0,           // a sanity variable during the construction stage
+            strip_indices: false,
+            leaf_indices: Vec::new(),
+            n_double_leaves: 0,
+            n_singular_leaves: 0,
+            wrap_forest: false,
+            escapes: false
         }
     }
 
@@ -106,13 +436,26 @@ impl String2StructureBuilder for String2Tree {
 
         // If the string is empty the algoritm has finished
         if input.is_empty() {
-            assert_eq!(self.level_balance, 0, "number of closers and openers don't match");
+            if self.level_balance != 0 {
+                return Err("number of closers and openers don't match".into());
+            }
+            if self.tree.root_node_id().is_none() {
+                return Err("found an empty input string".into());
+            }
             return Ok(());
         }
 
+        // on the first iteration, a forest is wrapped under a synthetic root if requested, and rejected otherwise
+        if self.tree.root_node_id().is_none() && String2Tree::count_top_level(input, self.escapes) > 1 {
+            if !self.wrap_forest {
+                return Err("found several top level trees, see set_wrap_forest to wrap them under a root".into());
+            }
+            *input = format!("{}{} {}{}", OPEN_BRACKETS, FOREST_ROOT, input.trim(), CLOSE_BRACKETS);
+        }
+
         // If constituency does not have open delimiter it's the last iteration, (work on right).
         // else, split by the delimeter (work on left, leave right for next iteration).
-        let (left, mut right) = match input.split_once(NODE_DELIMITER) {
+        let (left, mut right) = match split_once_unescaped(input, NODE_DELIMITER, self.escapes) {
             Some((left, right)) => (left.trim().to_owned(), right.trim().to_owned()),
             None => (input.trim().to_owned(), "".to_owned())
         };
@@ -137,20 +480,24 @@ impl String2StructureBuilder for String2Tree {
 
         // we have done a split by " ". We handle the left size and keep the right to next iter
         // we will validate and match the number of openers and closers in left. 
-        let mut closers = left.matches(CLOSE_BRACKETS).count();
-        let openers = left.matches(OPEN_BRACKETS).count();
-        assert!(openers <= 1, "invalid input structure, consecutive open brackets");
-        assert!(openers > 0 || closers > 0, "found a node without matching parenthesis");
+        let mut closers = count_unescaped(&left, CLOSE_BRACKETS, self.escapes);
+        let openers = count_unescaped(&left, OPEN_BRACKETS, self.escapes);
+        if openers > 1 {
+            return Err("invalid input structure, consecutive open brackets".into());
+        }
+        if openers == 0 && closers == 0 {
+            return Err("found a node without matching parenthesis".into());
+        }
         self.level_balance += openers as i32 - closers as i32;
         match closers {
             0 => {
 
                 // If closers = 0, it is an opening node, "(A" . 
-                // I asserted the number of openings to validate the structure.
+                // I validated the number of openings above.
                 // Create a new node and add to the tree
-                let node_str = left.trim_matches(OPEN_BRACKETS);
+                let node_str = unescape_label(strip_brackets(&left, self.escapes), self.escapes);
                 let parent_id = self.parent_node_id.as_ref();
-                let new_node_id = add_node(node_str, &parent_id)?;
+                let new_node_id = add_node(&node_str, &parent_id)?;
 
                 // make the new node the parent for next iteration
                 self.parent_node_id = Some(new_node_id);
@@ -159,12 +506,29 @@ impl String2StructureBuilder for String2Tree {
             _ => {
                 
                 // If closers > 0 , it is a leaf. it can look like "A)" or "(A)", depending on double or singular
-                let node_str = left.trim_matches(CLOSE_BRACKETS).trim_matches(OPEN_BRACKETS);
-                assert_ne!(node_str, "", "found a null node in input string");
+                let stripped = strip_brackets(&left, self.escapes);
+                if stripped.is_empty() {
+                    return Err("found a null node in input string".into());
+                }
+                let unescaped = unescape_label(stripped, self.escapes);
+                let mut node_str = unescaped.as_str();
+
+                // optionally strip an attached word index from the leaf and record it by leaf order
+                if self.strip_indices {
+                    let (label, index) = String2Tree::split_index(node_str);
+                    node_str = label;
+                    self.leaf_indices.push(index);
+                }
 
                 // Create a new node and add to the tree
                 let parent_id = self.parent_node_id.as_ref();
-                let new_node_id = add_node(&node_str, &parent_id)?;
+                let new_node_id = add_node(node_str, &parent_id)?;
+
+                // a leaf with an opener is singular "(A)", otherwise it is double "A)"
+                match openers {
+                    0 => self.n_double_leaves += 1,
+                    _ => self.n_singular_leaves += 1
+                }
 
                 // double or singular leaves change the requested parent for next iteration. In singular leaves,
                 // K closures mean that the parent for next iteration is K levels above. In double leaves,
@@ -192,7 +556,7 @@ impl String2StructureBuilder for String2Tree {
 #[cfg(test)]
 mod tests {
 
-    use super::String2Tree;
+    use super::{String2Tree, LeafStyle, is_balanced, first_imbalance, is_balanced_escaped, first_imbalance_escaped};
     use crate::generic_traits::generic_traits::String2StructureBuilder;
     use id_tree::{Node, PostOrderTraversal, LevelOrderTraversal, PreOrderTraversal};
     
@@ -255,6 +619,27 @@ mod tests {
         string2tree_template(example, golden, "pre");
     }
 
+    #[test]
+    fn carriage_return() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))\r\n";
+        let golden = vec!["S", "NP", "det", "The", "N", "people", "VP", "V", "watch", "NP", "det", "the", "N", "game"];
+        string2tree_template(example, golden, "pre");
+    }
+
+    #[test]
+    fn leaf_style() {
+        for (example, golden) in [
+            ("(S (NP (det The) (N people)) (VP (V watch)))", LeafStyle::Double),
+            ("(S (0 (1) (2 (3))))", LeafStyle::Singular),
+            ("(S (NP (det The)) (VP (V)))", LeafStyle::Mixed)
+        ] {
+            let mut constituency = String::from(example);
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.build(&mut constituency).unwrap();
+            assert_eq!(string2tree.get_leaf_style(), golden);
+        }
+    }
+
     #[test]
     fn math_mode() {
         let example = "(S (0 (1) (2 (3))))";
@@ -276,6 +661,92 @@ mod tests {
         string2tree_template(example, golden, "pre");
     }
 
+    #[test]
+    fn strip_indices() {
+        let mut constituency = String::from("(S (NP (det The_0) (N people_1)) (VP (V watch_2) (NP (det the) (N game_4))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_strip_indices(true);
+        string2tree.build(&mut constituency).unwrap();
+
+        let tree = string2tree.get_structure();
+        let root = tree.root_node_id().unwrap();
+        let prediction = tree.traverse_pre_order(root).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+        let golden = vec!["S", "NP", "det", "The", "N", "people", "VP", "V", "watch", "NP", "det", "the", "N", "game"];
+        assert_eq!(golden, prediction);
+        assert_eq!(string2tree.get_leaf_indices(), vec![Some(0), Some(1), Some(2), None, Some(4)]);
+    }
+
+    #[test]
+    fn wrap_forest() {
+        let mut constituency = String::from("(S (0)) (1 2)");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_wrap_forest(true);
+        string2tree.build(&mut constituency).unwrap();
+
+        let tree = string2tree.get_structure();
+        let root = tree.root_node_id().unwrap();
+        let prediction = tree.traverse_pre_order(root).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+        assert_eq!(prediction, vec!["ROOT", "S", "0", "1", "2"]);
+        assert_eq!(String2Tree::count_top_level("(S (NP (det The)) (VP (V)))", false), 1);
+
+        // without wrapping, a forest is an error
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        let error = string2tree.build(&mut String::from("(S (0)) (1 2)")).unwrap_err();
+        assert!(error.to_string().starts_with("found several top level trees"));
+    }
+
+    #[test]
+    fn reset() {
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_strip_indices(true);
+        string2tree.build(&mut String::from("(S (NP (det The_0) (N people_1)) (VP (V watch_2)))")).unwrap();
+        let capacity = string2tree.tree.capacity();
+
+        // the second tree is built as by a new builder, in the same allocation
+        string2tree.reset();
+        string2tree.build(&mut String::from("(S (0_0) (1_1))")).unwrap();
+        let tree = string2tree.get_structure();
+        let prediction = tree.traverse_pre_order(tree.root_node_id().unwrap()).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+        assert_eq!(prediction, vec!["S", "0", "1"]);
+        assert_eq!(string2tree.get_leaf_indices(), vec![Some(0), Some(1)]);
+        assert_eq!(string2tree.get_leaf_style(), LeafStyle::Singular);
+        assert_eq!(string2tree.tree.capacity(), capacity);
+    }
+
+    #[test]
+    fn balance() {
+        assert!(is_balanced("(S (0) (1 2))"));
+        assert!(is_balanced("(S (0)) (1 2)"));
+        assert!(is_balanced(""));
+        assert_eq!(first_imbalance(")(S (0))"), Some(0));
+        assert_eq!(first_imbalance("(S (0 (1"), Some(0));
+        assert_eq!(first_imbalance("(S (0 (1)) (2 (3)"), Some(0));
+        assert_eq!(first_imbalance("(S (0 (1))) (2 (3)"), Some(12));
+
+        // escaped brackets are part of the labels, an escaped escape is not
+        assert!(is_balanced_escaped("(PUNCT \\))"));
+        assert!(!is_balanced("(PUNCT \\))"));
+        assert_eq!(first_imbalance_escaped("(S (PUNCT \\() (X \\\\))"), None);
+        assert_eq!(first_imbalance_escaped("(S (X \\\\)))"), Some(10));
+    }
+
+    #[test]
+    fn escapes() {
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_escapes(true);
+        string2tree.build(&mut String::from("(NP (N New\\ York) (PUNCT \\)) (SYM \\\\))")).unwrap();
+        let tree = string2tree.get_structure();
+        let prediction = tree.traverse_pre_order(tree.root_node_id().unwrap()).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+        assert_eq!(prediction, vec!["NP", "N", "New York", "PUNCT", ")", "SYM", "\\"]);
+
+        // without escapes the backslash is an ordinary char
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut String::from("(N New\\York)")).unwrap();
+        let tree = string2tree.get_structure();
+        let prediction = tree.traverse_pre_order(tree.root_node_id().unwrap()).unwrap().map(|x| x.data().as_str()).collect::<Vec<&str>>();
+        assert_eq!(prediction, vec!["N", "New\\York"]);
+    }
+
     #[test]
     #[should_panic(expected = "found a null node in input string")]
     fn null_tree() {
@@ -303,8 +774,8 @@ mod tests {
     #[test]
     #[should_panic(expected = "inconsistent number of closers and ancestors for node id")]
     fn inconsistent_closers() {
-        let example = "(S (0)) (1 2)";
-        let golden = vec!["0", "1", "2"];
+        let example = "(S (0) (1)))";
+        let golden = vec!["S", "0", "1"];
         string2tree_template(example, golden, "pre");
     }
 
@@ -0,0 +1,62 @@
+//
+// Under MIT license
+//
+
+const CHAR_WIDTH_CONST: f32 = 0.6;  // approximated ratio between the width of a character and the font size
+const ELLIPSIS: char = '…';
+
+///
+/// A method that estimates the rendered width of a label in pixels, given the font size.
+/// The estimation is a monospace approximation, every character is taken as a constant ratio of the font size.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use parsed_to_plot::estimate_text_width;
+/// 
+/// let width = estimate_text_width("people", 15);
+/// assert!(width > estimate_text_width("the", 15));
+/// ```
+/// 
+pub fn estimate_text_width(label: &str, font_size: i32) -> f32 {
+    label.chars().count() as f32 * CHAR_WIDTH_CONST * font_size as f32
+}
+
+// A method that checks whether a label is estimated to be wider than the given width in pixels.
+// Used by the plotting modules to warn about labels that overlap their neighbors.
+pub(in crate) fn overflows(label: &str, cell_width: f32, font_size: i32) -> bool {
+    estimate_text_width(label, font_size) > cell_width
+}
+
+// A method that shortens a label with an ellipsis if it exceeds the given width in pixels,
+// based on the estimated width of the label given the font size.
+// Used by the plotting modules when label truncation is requested.
+pub(in crate) fn fit_label(label: &str, cell_width: f32, font_size: i32) -> String {
+    if !overflows(label, cell_width, font_size) {
+        return label.to_string();
+    }
+    let max_chars = (cell_width / estimate_text_width("-", font_size)).floor().max(2.0) as usize;
+    label.chars().take(max_chars - 1).chain(std::iter::once(ELLIPSIS)).collect::<String>()
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{estimate_text_width, overflows, fit_label};
+
+    #[test]
+    fn text_width() {
+        assert_eq!(estimate_text_width("", 15), 0.0);
+        assert_eq!(estimate_text_width("abc", 10), 18.0);
+        assert!(!overflows("abc", 18.0, 10));
+        assert!(overflows("abcd", 18.0, 10));
+    }
+
+    #[test]
+    fn fit() {
+        assert_eq!(fit_label("abc", 18.0, 10), "abc");
+        assert_eq!(fit_label("abcdef", 18.0, 10), "ab…");
+        assert!(estimate_text_width(&fit_label("internationalization", 60.0, 10), 10) <= 60.0);
+    }
+}
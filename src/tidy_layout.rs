@@ -0,0 +1,100 @@
+//
+// Under MIT license
+//
+
+// A Reingold-Tilford like layout of a tree, used by Tree2Plot for TreeLayout::Tidy. Subtrees are placed from left
+// to right as close as possible to their left siblings, such that nodes at the same depth are at least one unit
+// apart, and every parent is centered over its first and last children. The tree is given by the depths of its
+// nodes in pre order, so the subtree of a node is the contiguous range of the following deeper nodes.
+
+const MIN_SEPARATION: f32 = 1.0;
+
+// The left and right extents of a subtree in every depth, from the depth of its root downwards.
+type Contour = Vec<(f32, f32)>;
+
+// A method that returns the horizontal positions of the nodes (in units, the leftmost node at 0),
+// given their depths in pre order. The first node is the root.
+pub(in crate) fn tidy_positions(depths: &[usize]) -> Vec<f32> {
+
+    let mut xs = vec![0.0; depths.len()];
+    if depths.is_empty() {
+        return xs;
+    }
+    place(0, depths, &mut xs);
+
+    let min_x = xs.iter().cloned().fold(f32::INFINITY, f32::min);
+    xs.iter().map(|x| x - min_x).collect::<Vec<f32>>()
+}
+
+// A helper that returns the index after the last node in the subtree of the given node.
+fn subtree_end(node: usize, depths: &[usize]) -> usize {
+    (node + 1..depths.len()).find(|i| depths[*i] <= depths[node]).unwrap_or(depths.len())
+}
+
+// A helper that returns the children of the given node, the nodes one level deeper within its subtree.
+fn children_of(node: usize, depths: &[usize]) -> Vec<usize> {
+    (node + 1..subtree_end(node, depths)).filter(|i| depths[*i] == depths[node] + 1).collect::<Vec<usize>>()
+}
+
+// A recursive helper that places the subtree of the given node and returns its contour.
+fn place(node: usize, depths: &[usize], xs: &mut [f32]) -> Contour {
+
+    let children = children_of(node, depths);
+    if children.is_empty() {
+        xs[node] = 0.0;
+        return vec![(0.0, 0.0)];
+    }
+
+    // every child subtree is shifted right until it clears the contour of its left siblings in all common depths
+    let mut forest: Contour = Vec::new();
+    for child in children.iter() {
+        let contour = place(*child, depths, xs);
+        let shift = match forest.is_empty() {
+            true => 0.0,
+            false => forest.iter().zip(contour.iter())
+            .map(|(left, right)| left.1 + MIN_SEPARATION - right.0)
+            .fold(f32::NEG_INFINITY, f32::max)
+        };
+        for x in xs[*child..subtree_end(*child, depths)].iter_mut() {
+            *x += shift;
+        }
+
+        // merge the shifted contour into the contour of the placed siblings
+        for (level, (left, right)) in contour.into_iter().enumerate() {
+            match forest.get_mut(level) {
+                Some(extent) => *extent = (extent.0.min(left + shift), extent.1.max(right + shift)),
+                None => forest.push((left + shift, right + shift))
+            }
+        }
+    }
+
+    let x = (xs[children[0]] + xs[children[children.len() - 1]]) / 2.0;
+    xs[node] = x;
+    let mut contour = vec![(x, x)];
+    contour.extend(forest);
+    contour
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::tidy_positions;
+
+    #[test]
+    fn tidy() {
+        // (S (A (a) (b)) (B (c)))
+        assert_eq!(tidy_positions(&[0, 1, 2, 2, 1, 2]), vec![1.25, 0.5, 0.0, 1.0, 2.0, 2.0]);
+        assert_eq!(tidy_positions(&[0]), vec![0.0]);
+        assert!(tidy_positions(&[]).is_empty());
+    }
+
+    #[test]
+    fn tidy_compact() {
+        // (S (A (a (x) (y))) (b)): the leaf b only has to clear A at its own depth, not the deeper x and y
+        let xs = tidy_positions(&[0, 1, 2, 3, 3, 1]);
+        assert_eq!(xs[5] - xs[1], 1.0);
+        assert_eq!(xs[4] - xs[3], 1.0);
+    }
+
+}
@@ -8,23 +8,64 @@ use plotters::{prelude::*, style::text_anchor::*};
 use std::collections::HashMap;
 use std::error::Error;
 use std::ops::Deref;
+use std::sync::Arc;
 
+use plotters::coord::Shift;
 use super::generic_enums::{Accumulator, Element};
+use super::output_format::{BitmapPlotBackend, EncodingOptions, PlotFormat, PngCompression, OverlayChart, ChartOverlay, OverlayBackend, save_bitmap, save_pdf, validate_font, validate_png_keyword};
+use super::warnings::Warning;
+use super::text_layout::{estimate_text_width, fit_label, overflows};
+use super::tidy_layout::tidy_positions;
 use super::sub_tree_children::sub_tree_children::SubChildren;
 use super::generic_traits::generic_traits::{Structure2PlotBuilder, Structure2PlotPlotter, WalkActions, WalkTree};
 
 const DIM_CONST: usize = 640;
+const MIN_DIM: u32 = 100;
+const MAX_ASPECT: u32 = 2;          // maximal ratio of height to length, for narrow trees like chains
 const FONT_CONST: f32 = 0.0267;
 const FONT_SIZE: u32 = 15;
 const INIT_LEFT_BOUND: f32 = -5.0;  // left and right bound are arbitrary, not shown on plot, only used for numeric ratios
+                                    // (the width of the figure is set by set_horizontal_stretch, not by the bounds)
 const INIT_RIGHT_BOUND: f32 = 5.0;
 const Y_AX_LABEL: &str = "Depth";
+const X_LABEL_AREA: u32 = 10;
+const Y_LABEL_AREA: u32 = 50;
+const GRIDLINE_OPACITY: f64 = 0.15;
+const SEPARATOR_OPACITY: f64 = 0.3;  // the opacity of the lines between the trees of a forest, see set_forest_separators
+const SUBSCRIPT_SCALE: f32 = 0.7;   // the font size of a subscript index relative to its label, see set_subscript_separator
+const SUBSCRIPT_DROP: f32 = 0.35;   // how far a subscript index is lowered, relative to the font size of its label
+
+/// An enum of the layouts by which the nodes of a tree are positioned.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TreeLayout {
+    /// every node is centered in a width proportional to the number of leaves in its subtree (the default)
+    Proportional,
+    /// the leaves are equally spaced along the bottom level, and every parent is centered over its children
+    EqualLeaves,
+    /// a Reingold-Tilford like layout, subtrees are packed as close as possible without overlapping,
+    /// and every parent is centered over its children. Narrower than the other layouts for bushy trees.
+    Tidy
+}
+
+/// An enum of the routings by which the edges from a parent to its children are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeStyle {
+    /// a straight line from the parent to every child, fanning out (the default)
+    Straight,
+    /// a short vertical drop from the parent, a horizontal line shared by the children and a vertical line
+    /// down to every child, as in many linguistics textbook figures
+    Orthogonal
+}
+
+// A user callback that formats the label of a node before it is drawn, see set_label_format.
+type LabelFormat = Arc<dyn Fn(&str) -> String + Send + Sync>;
 
 // A struct that wraps the needed fields to plot a node - the positional location on the plot and the label.
 #[derive(Clone, Debug)]
 pub(in crate) struct TreePlotData {
     positional_args: [f32; 6],  // save x1 y1 x2 y2 left_bound right_bound
     label_arg: String,          // save label
+    is_leaf: bool               // whether the node is drawn as a leaf (without children)
 }
 
 /*
@@ -36,9 +77,464 @@ The reason is that this module is based on two components:
 
 /// A Tree2Plot struct, mainly holds the tree object. This type will implement Structure2PlotBuilder, Structure2PlotPlotter,
 /// WalkTree and WalkActions, with an ultimate goal of saving a plot of the tree to file.
+/// A configured Tree2Plot can be cloned to plot variants of the same tree, the clones share the callbacks.
+/// The tree of a clone has its own node ids, so node ids of the original tree are not valid for set_sub_root of a clone.
  pub struct Tree2Plot {
     tree: Tree<String>,
-    node_id2n_sub_children: HashMap<NodeId, usize>
+    node_id2n_sub_children: HashMap<NodeId, usize>,
+    warnings: Vec<Warning>,
+    margin: u32,
+    truncate_labels: bool,
+    min_level_spacing: Option<u32>,
+    min_leaf_spacing: Option<f32>,
+    background: RGBColor,
+    foreground: RGBColor,
+    scale: f32,
+    horizontal_stretch: f32,
+    merge_preterminals: bool,
+    encoding: EncodingOptions,
+    label_format: Option<LabelFormat>,
+    leaf_color: Option<RGBColor>,
+    center_parents: bool,
+    depth_gridlines: bool,
+    layout: TreeLayout,
+    edge_style: EdgeStyle,
+    subscript_separator: Option<String>,
+    forest_separators: bool,
+    overlay: Option<ChartOverlay>
+}
+
+impl Tree2Plot {
+
+    ///
+    /// A set method to determine the margin around the plot area, in pixels (default 15).
+    /// Increase it if labels near the edges of the image get clipped.
+    /// 
+    pub fn set_margin(&mut self, margin: u32) {
+        self.margin = margin;
+    }
+
+    ///
+    /// A set method to determine whether labels wider than their allocated cell are truncated with an
+    /// ellipsis (default false). Without truncation, long leaf labels may overlap their neighbors.
+    /// 
+    pub fn set_truncate_labels(&mut self, truncate_labels: bool) {
+        self.truncate_labels = truncate_labels;
+    }
+
+    ///
+    /// A set method to determine a minimal vertical spacing between adjacent depth levels, in pixels (default None).
+    /// When the computed figure is too short to keep this spacing, its height is grown (the font size is kept),
+    /// so that labels of deep trees at adjacent levels do not overlap.
+    /// 
+    pub fn set_min_level_spacing(&mut self, min_level_spacing: Option<u32>) {
+        self.min_level_spacing = min_level_spacing;
+    }
+
+    ///
+    /// A set method to determine a minimal horizontal spacing allocated to every leaf, as a multiple of the font size
+    /// (default None, no check). Very unbalanced trees may leave some leaves cramped, those are counted in a
+    /// Warning::CrampedLeaves after build, a hint to switch to the equal leaves or tidy layout, or to stretch the figure.
+    /// 
+    pub fn set_min_leaf_spacing(&mut self, min_leaf_spacing: Option<f32>) {
+        self.min_leaf_spacing = min_leaf_spacing;
+    }
+
+    ///
+    /// A set method to determine the background color of the figure as an rgb value (default white).
+    /// 
+    pub fn set_background_color(&mut self, rgb: (u8, u8, u8)) {
+        self.background = RGBColor(rgb.0, rgb.1, rgb.2);
+    }
+
+    ///
+    /// A set method to determine the color of the labels, edges and axis as an rgb value (default black).
+    /// Use together with set_background_color for dark themed figures.
+    /// 
+    pub fn set_foreground_color(&mut self, rgb: (u8, u8, u8)) {
+        self.foreground = RGBColor(rgb.0, rgb.1, rgb.2);
+    }
+
+    ///
+    /// A set method to determine the color of the leaf labels as an rgb value (default None, leaves are drawn
+    /// in the foreground color like the inner nodes). Useful to tell operands from operators in expression trees.
+    /// 
+    pub fn set_leaf_color(&mut self, rgb: Option<(u8, u8, u8)>) {
+        self.leaf_color = rgb.map(|(r, g, b)| RGBColor(r, g, b));
+    }
+
+    ///
+    /// A set method to determine a uniform scale factor of the figure (default 1.0), for high resolution images.
+    /// The computed dimensions, font size, margins and line widths are all multiplied by the factor,
+    /// so the layout is kept as in the default figure. Returns an error if the factor is not a positive number.
+    /// 
+    pub fn set_scale(&mut self, scale: f32) -> Result<(), Box<dyn Error>> {
+        if !(scale.is_finite() && scale > 0.0) {
+            return Err(format!("the scale should be a positive number, got {}", scale).into());
+        }
+        self.scale = scale;
+        Ok(())
+    }
+
+    ///
+    /// A set method to determine a factor by which the width of the figure is stretched (default 1.0), to spread
+    /// the nodes of a tree horizontally (or squeeze them with a factor below 1). The height and the font size are kept.
+    /// The nodes are laid out in the internal bounds -5 to 5 on the x axis regardless of the stretch.
+    /// Returns an error if the factor is not a positive number.
+    /// 
+    pub fn set_horizontal_stretch(&mut self, horizontal_stretch: f32) -> Result<(), Box<dyn Error>> {
+        if !(horizontal_stretch.is_finite() && horizontal_stretch > 0.0) {
+            return Err(format!("the horizontal stretch should be a positive number, got {}", horizontal_stretch).into());
+        }
+        self.horizontal_stretch = horizontal_stretch;
+        Ok(())
+    }
+
+    ///
+    /// A set method to determine whether preterminals (nodes whose only child is a leaf, like the det in (det The))
+    /// are merged with their leaf into a single node labeled by both, one above the other (default false).
+    /// This produces the compact look of syntax trees, saving a depth level in double-leaf trees.
+    /// 
+    pub fn set_merge_preterminals(&mut self, merge_preterminals: bool) {
+        self.merge_preterminals = merge_preterminals;
+    }
+
+    ///
+    /// A set method to determine whether every parent is re-centered over the midpoint of its first and last
+    /// children (default false). By default a node is centered in the space allocated to its subtree, which can
+    /// look off-center above asymmetric subtrees. Leaves keep their positions.
+    /// 
+    pub fn set_center_parents(&mut self, center_parents: bool) {
+        self.center_parents = center_parents;
+    }
+
+    ///
+    /// A set method to determine whether faint horizontal rules are drawn behind the tree at every depth (default false),
+    /// to help estimating the depths of the nodes in complex trees.
+    /// 
+    pub fn set_depth_gridlines(&mut self, depth_gridlines: bool) {
+        self.depth_gridlines = depth_gridlines;
+    }
+
+    ///
+    /// A set method to determine whether a light vertical line is drawn between the subtrees of the root (default false),
+    /// meant for the trees of a forest that String2Tree::set_wrap_forest wraps under a synthetic root, to tell the
+    /// sentences of a combined image apart. A line is drawn halfway between adjacent subtrees, below the root, and is
+    /// left out where the subtrees overlap horizontally (which the tidy layout allows).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut constituency = String::from("(S (NP (N People)) (VP (V watch))) (S (NP (N Time)) (VP (V flies)))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.set_wrap_forest(true);
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.set_forest_separators(true);
+    /// tree2plot.build("Output/constituency_forest.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_forest_separators(&mut self, forest_separators: bool) {
+        self.forest_separators = forest_separators;
+    }
+
+    ///
+    /// A set method to determine the layout by which the nodes are positioned (default TreeLayout::Proportional).
+    /// Layouts other than the proportional one always center the parents, regardless of set_center_parents.
+    /// 
+    pub fn set_layout(&mut self, layout: TreeLayout) {
+        self.layout = layout;
+    }
+
+    ///
+    /// A set method to determine how the edges from a parent to its children are routed (default EdgeStyle::Straight).
+    /// 
+    pub fn set_edge_style(&mut self, edge_style: EdgeStyle) {
+        self.edge_style = edge_style;
+    }
+
+    ///
+    /// A set method to determine a separator of a subscript index in the labels (default None, labels are drawn as is).
+    /// A label is split at the last separator, and the index after it is drawn smaller and lowered, as co-indexed nodes
+    /// are marked in linguistics figures: with "-", NP-1 is drawn as NP with a subscript 1. Only a numeric index is a
+    /// subscript, so function tags like NP-SBJ and labels like -LRB- are drawn as is, and so are the words of the leaves
+    /// (like well-known). The tree itself is not changed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut constituency = String::from("(S (NP-1 (N John)) (VP (V seems) (S (NP-1 (N e)) (VP (V to) (VP (V leave))))))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.set_subscript_separator(Some("-"));
+    /// tree2plot.build("Output/constituency_subscript.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_subscript_separator(&mut self, subscript_separator: Option<&str>) {
+        self.subscript_separator = subscript_separator.map(|x| x.to_string());
+    }
+
+    ///
+    /// A set method to determine a callback that formats the label of every node before it is drawn
+    /// (by default the label is drawn as is), for example to abbreviate long labels. The tree itself is not changed.
+    /// A merged preterminal (see set_merge_preterminals) has its two labels formatted separately.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.set_label_format(|label| label.to_uppercase());
+    /// tree2plot.build("Output/constituency_uppercase.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_label_format<F>(&mut self, label_format: F)
+    where F: Fn(&str) -> String + Send + Sync + 'static {
+        self.label_format = Some(Arc::new(label_format));
+    }
+
+    ///
+    /// A set method to determine a callback that draws additional series over the plot (by default nothing is drawn),
+    /// for example a highlight rectangle or an arrow. The callback gets the chart after the tree is drawn and before
+    /// the image is saved. The x axis spans -5 to 5 from left to right, and the y axis is the depth (0 at the top).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::{OverlayChart, OverlayCoord};
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// use plotters::prelude::*;
+    /// use std::error::Error;
+    /// 
+    /// fn highlight<DB: DrawingBackend>(chart: &mut ChartContext<DB, OverlayCoord>) -> Result<(), Box<dyn Error>>
+    /// where DB::ErrorType: 'static {
+    ///     chart.draw_series(std::iter::once(Rectangle::new([(-5.0, 0.8), (0.0, 1.2)], RED.stroke_width(2))))?;
+    ///     Ok(())
+    /// }
+    /// 
+    /// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.set_overlay(|chart| match chart {
+    ///     OverlayChart::Bitmap(chart) => highlight(chart),
+    ///     OverlayChart::Svg(chart) => highlight(chart)
+    /// });
+    /// tree2plot.build("Output/constituency_overlay.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_overlay<F>(&mut self, overlay: F)
+    where F: Fn(OverlayChart) -> Result<(), Box<dyn Error>> + Send + Sync + 'static {
+        self.overlay = Some(Arc::new(overlay));
+    }
+
+    ///
+    /// A method to plot only the subtree of the given node, which becomes the root of the plot.
+    /// The node id should belong to the tree that was given to new. See also set_sub_root_by_label.
+    /// 
+    pub fn set_sub_root(&mut self, node_id: &NodeId) -> Result<(), Box<dyn Error>> {
+
+        // copy the subtree into a new tree, each node is inserted under the copy of its parent (pre order)
+        let mut sub_tree: Tree<String> = TreeBuilder::new().build();
+        let mut old2new: HashMap<NodeId, NodeId> = HashMap::new();
+        for old_id in self.tree.traverse_pre_order_ids(node_id)? {
+            let node = Node::new(self.tree.get(&old_id)?.data().clone());
+            let new_id = match old2new.is_empty() {
+                true => sub_tree.insert(node, InsertBehavior::AsRoot)?,
+                false => {
+                    let old_parent_id = self.tree.get(&old_id)?.parent().ok_or("found a node without a parent in a subtree")?;
+                    sub_tree.insert(node, InsertBehavior::UnderNode(&old2new[old_parent_id]))?
+                }
+            };
+            old2new.insert(old_id, new_id);
+        }
+
+        self.node_id2n_sub_children = sub_tree.get_sub_children(true)?;
+        self.tree = sub_tree;
+        Ok(())
+    }
+
+    ///
+    /// A method to plot only the subtree of the first node (in pre order) whose label matches the given label.
+    /// Returns an error if no node matches.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.set_sub_root_by_label("VP").unwrap();
+    /// tree2plot.build("Output/constituency_vp.png").unwrap();
+    /// ```
+    /// 
+    pub fn set_sub_root_by_label(&mut self, label: &str) -> Result<(), Box<dyn Error>> {
+        let root_id = self.tree.root_node_id().ok_or("tree is empty")?;
+        let node_id = self.tree.traverse_pre_order_ids(root_id)?
+        .find(|node_id| self.tree.get(node_id).map(|node| node.data() == label).unwrap_or(false))
+        .ok_or(format!("no node with label {} was found in the tree", label))?;
+        self.set_sub_root(&node_id)
+    }
+
+    ///
+    /// A set method to determine the compression level of png outputs (default PngCompression::Fast).
+    /// Higher levels produce smaller files at the cost of slower encoding, useful for large batch runs.
+    /// 
+    pub fn set_png_compression(&mut self, png_compression: PngCompression) {
+        self.encoding.png_compression = png_compression;
+    }
+
+    ///
+    /// A set method to determine the quality of jpeg outputs (saved to .jpg or .jpeg paths), in the range 1-100 (default 75).
+    /// 
+    pub fn set_jpeg_quality(&mut self, jpeg_quality: u8) {
+        self.encoding.jpeg_quality = jpeg_quality;
+    }
+
+    ///
+    /// A set method to determine the time in milliseconds that every frame of a gif output is shown (default 500).
+    /// A tree saved to a .gif path is animated, it is built node by node in the order of the parsed string
+    /// (pre order), one frame per node.
+    /// 
+    pub fn set_gif_frame_delay(&mut self, gif_frame_delay: u32) {
+        self.encoding.gif_frame_delay = gif_frame_delay;
+    }
+
+    ///
+    /// A set method to determine whether bitmap outputs (png, jpeg, gif and other bitmap formats) are anti-aliased (default true).
+    /// Without anti-aliasing every pixel is drawn in one of the colors of the plot, so the same plot produces the same image
+    /// on every platform, useful for image regression tests. Svg and pdf outputs are not affected.
+    /// 
+    pub fn set_antialiasing(&mut self, antialiasing: bool) {
+        self.encoding.antialiasing = antialiasing;
+    }
+
+    ///
+    /// A set method to determine a font that is embedded in bitmap outputs (png, jpeg, gif and other bitmap formats)
+    /// for all texts, as the bytes of a TrueType or OpenType font file (default None, the sans-serif font of the system).
+    /// The system font differs across machines, an embedded font (e.g. by include_bytes) gives the same text everywhere.
+    /// A public domain font is bundled with the bundled_font feature, as BUNDLED_FONT. Svg and pdf outputs are not affected.
+    /// Returns an error if the bytes are not a valid font.
+    /// 
+    pub fn set_font(&mut self, font: Option<&'static [u8]>) -> Result<(), Box<dyn Error>> {
+        if let Some(font) = font {
+            validate_font(font)?;
+        }
+        self.encoding.font = font;
+        Ok(())
+    }
+
+    ///
+    /// A method to compute the dimensions in pixels (width, height) of the image that build would save, without drawing
+    /// or writing anything. Useful to lay out several figures, or to check their sizes before saving them.
+    /// 
+    pub fn compute_dimensions(&self) -> Result<(u32, u32), Box<dyn Error>> {
+        let (plot_data_vec, tree_length) = self.layout()?;
+        let (fig_dims, _, _) = self.figure_dimensions(Tree2Plot::tree_height(&plot_data_vec), tree_length);
+        Ok(fig_dims)
+    }
+
+    ///
+    /// A method to add a text entry to the metadata of png outputs, for example the parsed string as the "Description",
+    /// so the image is self-describing. Every call adds an entry, saved as a text chunk of the png (iTXt) that image
+    /// viewers and tools like exiftool show. The keyword should be 1-79 printable ascii characters, otherwise an error is
+    /// returned and the entry is not added. Other output formats are not affected.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2Plot;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+    /// let mut constituency = String::from(example);
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2plot.add_png_text("Description", example).unwrap();
+    /// tree2plot.build("Output/constituency_described.png").unwrap();
+    /// ```
+    /// 
+    pub fn add_png_text(&mut self, keyword: &str, text: &str) -> Result<(), Box<dyn Error>> {
+        validate_png_keyword(keyword)?;
+        self.encoding.png_texts.push((keyword.to_string(), text.to_string()));
+        Ok(())
+    }
+
+    ///
+    /// A get method to retrieve the warnings accumulated during the last build.
+    /// 
+    pub fn get_warnings(&self) -> Vec<Warning> {
+        self.warnings.clone()
+    }
+}
+
+impl Clone for Tree2Plot {
+
+    // a cloned tree has new node ids, so the sub children are mapped again for the cloned tree
+    fn clone(&self) -> Self {
+        let mut tree = self.tree.clone();
+        let node_id2n_sub_children = tree.get_sub_children(true).expect("the sub children were already mapped for the same tree");
+        Self {
+            tree,
+            node_id2n_sub_children,
+            warnings: self.warnings.clone(),
+            margin: self.margin,
+            truncate_labels: self.truncate_labels,
+            min_level_spacing: self.min_level_spacing,
+            min_leaf_spacing: self.min_leaf_spacing,
+            background: self.background,
+            foreground: self.foreground,
+            scale: self.scale,
+            horizontal_stretch: self.horizontal_stretch,
+            merge_preterminals: self.merge_preterminals,
+            encoding: self.encoding.clone(),
+            label_format: self.label_format.clone(),
+            leaf_color: self.leaf_color,
+            center_parents: self.center_parents,
+            depth_gridlines: self.depth_gridlines,
+            layout: self.layout,
+            edge_style: self.edge_style,
+            subscript_separator: self.subscript_separator.clone(),
+            forest_separators: self.forest_separators,
+            overlay: self.overlay.clone()
+        }
+    }
 }
 
 impl Structure2PlotBuilder<Tree<String>> for Tree2Plot {
@@ -53,55 +549,63 @@ impl Structure2PlotBuilder<Tree<String>> for Tree2Plot {
 
         Self {
             node_id2n_sub_children: node_id2n_sub_children,
-            tree: structure
+            tree: structure,
+            warnings: Vec::new(),
+            margin: FONT_SIZE,
+            truncate_labels: false,
+            min_level_spacing: None,
+            min_leaf_spacing: None,
+            background: WHITE,
+            foreground: BLACK,
+            scale: 1.0,
+            horizontal_stretch: 1.0,
+            merge_preterminals: false,
+            encoding: EncodingOptions::default(),
+            label_format: None,
+            leaf_color: None,
+            center_parents: false,
+            depth_gridlines: false,
+            layout: TreeLayout::Proportional,
+            edge_style: EdgeStyle::Straight,
+            subscript_separator: None,
+            forest_separators: false,
+            overlay: None
         }
     }
 
     /// See examples on how to use this function on lib.rs
     fn build(&mut self, save_to: &str) -> Result<(), Box<dyn Error>> {
         
-        // run the recursive extraction
-        let mut accumulator = Accumulator::TPD(Vec::<TreePlotData>::new());
-        self.walk(None, &mut accumulator)?;
+        self.warnings.clear();
+        let (plot_data_vec, tree_length) = self.layout()?;
+        let tree_height = Tree2Plot::tree_height(&plot_data_vec);
+        let (fig_dims, font_size, clamped) = self.figure_dimensions(tree_height, tree_length);
+        self.warnings.extend(clamped);
+        let font_style: (&str, i32) = ("sans-serif", font_size);
 
-        // calculate dimensions of plot based on tree height and number of leaf-children in sub tree
-        let tree_height = self.tree.height();
-        let tree_length = self.node_id2n_sub_children.get(self.tree.root_node_id().unwrap()).unwrap();
-        let height = (DIM_CONST * tree_height / tree_length) as u32;
-        let length = (DIM_CONST * tree_length / tree_height) as u32;
-        let fig_dims: (u32, u32) = (length, height);
-        let font_style: (&str, i32) = ("sans-serif", ((height as f32) * FONT_CONST) as i32);
-
-        // initialization of backend settings
-        let root_area = BitMapBackend::new(save_to, fig_dims).into_drawing_area();
-        root_area.fill(&WHITE).unwrap();
-        let x_spec = std::ops::Range{start:INIT_LEFT_BOUND, end:INIT_RIGHT_BOUND};
-        let y_spec = std::ops::Range{start:(tree_height-1) as f32, end: 0.0};
-
-        // x axis is removed thus doesn't need much space compared to y axis
-        let mut chart = ChartBuilder::on(&root_area)
-        .margin(FONT_SIZE)
-        .x_label_area_size(10)
-        .y_label_area_size(50)
-        .build_cartesian_2d(x_spec, y_spec).unwrap();
-        
-        chart
-        .configure_mesh()
-        .bold_line_style(&BLACK)
-        .disable_x_mesh()
-        .disable_y_mesh()
-        .disable_x_axis()
-        .y_labels(tree_height as usize)
-        .y_desc(Y_AX_LABEL)
-        .y_label_style(font_style)
-        .axis_desc_style(font_style)
-        .y_label_formatter(&|x| format!("{}", *x as i32))
-        .draw()
-        .unwrap();
-
-        let plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator)?;
-        self.plot(&mut chart, plot_data_vec.deref().to_vec(), font_style)?;
-        Ok(())
+        // initialization of the backend by the extension of save_to (png by default, jpeg, svg or pdf)
+        match PlotFormat::from_path(save_to) {
+            format @ (PlotFormat::Png | PlotFormat::Jpeg) => {
+                let mut buffer = vec![0; (fig_dims.0 * fig_dims.1 * 3) as usize];
+                let backend = BitMapBackend::with_buffer(&mut buffer, fig_dims);
+                self.draw(BitmapPlotBackend::new(backend, &self.encoding)?.into_drawing_area(), plot_data_vec, tree_height, font_style)?;
+                save_bitmap(&buffer, fig_dims, save_to, &format, &self.encoding)
+            },
+            PlotFormat::Gif => {
+                let backend = BitMapBackend::gif(save_to, fig_dims, self.encoding.gif_frame_delay)?;
+                self.draw_frames(BitmapPlotBackend::new(backend, &self.encoding)?.into_drawing_area(), plot_data_vec, tree_height, font_style)
+            },
+            PlotFormat::Bitmap => {
+                let backend = BitMapBackend::new(save_to, fig_dims);
+                self.draw(BitmapPlotBackend::new(backend, &self.encoding)?.into_drawing_area(), plot_data_vec, tree_height, font_style)
+            },
+            PlotFormat::Svg => self.draw(SVGBackend::new(save_to, fig_dims).into_drawing_area(), plot_data_vec, tree_height, font_style),
+            PlotFormat::Pdf => {
+                let mut svg = String::new();
+                self.draw(SVGBackend::with_string(&mut svg, fig_dims).into_drawing_area(), plot_data_vec, tree_height, font_style)?;
+                save_pdf(&svg, save_to)
+            }
+        }
 
     }
 
@@ -116,29 +620,86 @@ impl Structure2PlotPlotter<TreePlotData> for Tree2Plot {
         let text_style = TextStyle::from(font_style)
         .transform(FontTransform::None)
         .font.into_font().style(FontStyle::Bold)
-        .with_color(&BLACK)
+        .with_color(&self.foreground)
         .with_anchor::<RGBColor>(Pos::new(HPos::Center, VPos::Center))
         .into_text_style(chart.plotting_area());
+        let leaf_color = self.leaf_color.unwrap_or(self.foreground);
+        let leaf_text_style = text_style.color(&leaf_color);
+
+        // the width of the plotting area in pixels, used to convert the allocated bounds to pixels
+        let area_width = chart.plotting_area().dim_in_pixel().0 as f32;
 
         for plot_data in plot_data_vec {
             
             // extracting plot location 
-            let label = &plot_data.label_arg;
-            let [x1, y1, x2, y2]: [f32; 4] = plot_data.positional_args[..4].try_into().unwrap();
+            let [x1, y1, x2, y2, left_bound, right_bound]: [f32; 6] = plot_data.positional_args;
+            let lines = self.format_label(&plot_data.label_arg).into_iter().map(|line| match self.truncate_labels {
+                true => {
+                    let cell_width = area_width * (right_bound - left_bound) / (INIT_RIGHT_BOUND - INIT_LEFT_BOUND);
+                    fit_label(&line, cell_width, font_style.1)
+                },
+                false => line
+            }).collect::<Vec<String>>();
 
             // order matters - lines before circles before text.
             // plus 0.1 is a workaround for visualization purposes
-            chart.draw_series(LineSeries::new(vec![(x1, y1+0.1), (x2, y2-0.1)], &BLACK)).unwrap();
+            // the root has no incoming edge (its positional args start and end at the same point)
+            // an orthogonal edge turns halfway between the levels, where the edges of all the siblings meet
+            if y2 > y1 {
+                let edge = match self.edge_style {
+                    EdgeStyle::Straight => vec![(x1, y1+0.1), (x2, y2-0.1)],
+                    EdgeStyle::Orthogonal => {
+                        let y_turn = (y1 + y2) / 2.0;
+                        vec![(x1, y1+0.1), (x1, y_turn), (x2, y_turn), (x2, y2-0.1)]
+                    }
+                };
+                chart.draw_series(LineSeries::new(edge, self.foreground.stroke_width(self.scaled(1)))).unwrap();
+            }
             chart.draw_series(PointSeries::of_element(
                 vec![(x2, y2)],
                 FONT_SIZE,
-                &BLACK,
+                &self.foreground,
                 &|c, _s, _st| {
                     return EmptyElement::at(c)
-                    + Circle::new((0, 0), 10, ShapeStyle{color: WHITE.into(), filled: true, stroke_width: 1})
-                    + Text::new(format!("{}", label), (0,0), &text_style);
+                    + Circle::new((0, 0), self.scaled(10), ShapeStyle{color: self.background.into(), filled: true, stroke_width: self.scaled(1)});
                 },
             )).unwrap();
+
+            // a label of a merged preterminal has two lines, they are centered together around the node
+            let node_text_style = match plot_data.is_leaf {
+                true => &leaf_text_style,
+                false => &text_style
+            };
+            for (i, line) in lines.iter().enumerate() {
+                let offset = ((2 * i as i32 + 1 - lines.len() as i32) * font_style.1) / 2;
+                let is_word = plot_data.is_leaf && i + 1 == lines.len();
+                match self.split_subscript(line).filter(|_| !is_word) {
+                    Some((label, index)) => {
+
+                        // the label and its index are centered together around the node, the index is smaller and lowered
+                        let index_size = (font_style.1 as f32 * SUBSCRIPT_SCALE) as i32;
+                        let index_style = TextStyle { font: node_text_style.font.resize(index_size as f64), ..node_text_style.clone() };
+                        let (label_width, index_width) = (estimate_text_width(label, font_style.1), estimate_text_width(index, index_size));
+                        let (label_x, index_x) = ((-index_width / 2.0) as i32, (label_width / 2.0) as i32);
+                        let index_offset = offset + (font_style.1 as f32 * SUBSCRIPT_DROP) as i32;
+                        chart.draw_series(PointSeries::of_element(
+                            vec![(x2, y2)],
+                            FONT_SIZE,
+                            &self.foreground,
+                            &|c, _s, _st| EmptyElement::at(c) + Text::new(label.to_string(), (label_x, offset), node_text_style)
+                            + Text::new(index.to_string(), (index_x, index_offset), &index_style),
+                        )).unwrap();
+                    },
+                    None => {
+                        chart.draw_series(PointSeries::of_element(
+                            vec![(x2, y2)],
+                            FONT_SIZE,
+                            &self.foreground,
+                            &|c, _s, _st| EmptyElement::at(c) + Text::new(line.clone(), (0, offset), node_text_style),
+                        )).unwrap();
+                    }
+                }
+            }
         }
 
         Ok(())
@@ -146,6 +707,276 @@ impl Structure2PlotPlotter<TreePlotData> for Tree2Plot {
 
 }
 
+impl Tree2Plot {
+
+    // A helper method that runs the recursive extraction of the plotting data, and positions the nodes by the layout.
+    // Returns the plotting data and the length of the tree, the number of leaves or the number of units of the tidy layout.
+    fn layout(&self) -> Result<(Vec<TreePlotData>, usize), Box<dyn Error>> {
+        let mut accumulator = Accumulator::TPD(Vec::<TreePlotData>::new());
+        self.walk(None, &mut accumulator)?;
+        let mut plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator)?.deref().to_vec();
+        let n_leaves = *self.node_id2n_sub_children.get(self.tree.root_node_id().ok_or("tree is empty")?).ok_or("didn't find the root in mapping to sub children")?;
+
+        let tree_length = match self.layout {
+            TreeLayout::Proportional if self.center_parents => {
+                Tree2Plot::center_parents(&mut plot_data_vec);
+                n_leaves
+            },
+            TreeLayout::Proportional => n_leaves,
+            TreeLayout::EqualLeaves => {
+                Tree2Plot::center_parents(&mut plot_data_vec);
+                Tree2Plot::align_leaves(&mut plot_data_vec);
+                n_leaves
+            },
+            TreeLayout::Tidy => Tree2Plot::tidy(&mut plot_data_vec)
+        };
+        Ok((plot_data_vec, tree_length))
+    }
+
+    // A helper method that returns the number of levels of the plot. The height is taken from the walk, since
+    // merged preterminals may save the deepest level.
+    fn tree_height(plot_data_vec: &[TreePlotData]) -> usize {
+        plot_data_vec.iter().map(|plot_data| plot_data.positional_args[3] as usize).max().unwrap_or(0) + 1
+    }
+
+    // A helper method that calculates the dimensions of the figure based on the tree height and tree length.
+    // Returns the dimensions in pixels, the font size, and a warning if the dimensions were clamped.
+    fn figure_dimensions(&self, tree_height: usize, tree_length: usize) -> ((u32, u32), i32, Option<Warning>) {
+        let height = (DIM_CONST * tree_height / tree_length) as u32;
+        let length = (DIM_CONST * tree_length / tree_height) as u32;
+
+        // narrow trees (like a root with a single leaf) are limited in height, to avoid a thin and long figure
+        let capped_height = height.min((MAX_ASPECT * length).max(DIM_CONST as u32 / 2));
+
+        // the width is stretched after the height is capped, so the stretch does not change the height
+        let length = (length as f32 * self.horizontal_stretch).round() as u32;
+        let fig_dims: (u32, u32) = (length.max(MIN_DIM), capped_height.max(MIN_DIM));
+        let clamped = match fig_dims != (length, height) {
+            true => Some(Warning::DimensionsClamped((length, height), fig_dims)),
+            false => None
+        };
+        let height = fig_dims.1;
+        let font_size = ((height as f32) * FONT_CONST * self.scale) as i32;
+
+        // grow the height to keep the minimal spacing between levels, on top of the margins and the x label area
+        let fig_dims = match self.min_level_spacing {
+            Some(spacing) => (fig_dims.0, height.max(spacing * tree_height as u32 + 2 * self.margin + X_LABEL_AREA)),
+            None => fig_dims
+        };
+        ((self.scaled(fig_dims.0), self.scaled(fig_dims.1)), font_size, clamped)
+    }
+
+    // A helper method that multiplies a size in pixels by the scale factor of the figure.
+    fn scaled(&self, size: u32) -> u32 {
+        (size as f32 * self.scale).round() as u32
+    }
+
+    // A helper method that returns the x positions of the separators between the subtrees of the root, see
+    // set_forest_separators. In pre order, a subtree starts at a node of depth 1 and holds the deeper nodes after it.
+    // A separator is halfway between the rightmost node of a subtree and the leftmost node of the next one.
+    fn forest_separators(plot_data_vec: &[TreePlotData]) -> Vec<f32> {
+        let mut extents: Vec<(f32, f32)> = Vec::new();
+        for plot_data in plot_data_vec {
+            let (x, depth) = (plot_data.positional_args[2], plot_data.positional_args[3]);
+            if depth == 1.0 {
+                extents.push((x, x));
+            } else if let (true, Some(extent)) = (depth > 1.0, extents.last_mut()) {
+                *extent = (extent.0.min(x), extent.1.max(x));
+            }
+        }
+        extents.windows(2)
+        .filter(|pair| pair[0].1 < pair[1].0)
+        .map(|pair| (pair[0].1 + pair[1].0) / 2.0)
+        .collect::<Vec<f32>>()
+    }
+
+    // A helper method that re-centers every parent over the midpoint of its first and last children, as a second pass
+    // over the walk output. The output is in pre order, so the children of a node are the following nodes one level
+    // deeper, up to the next node that is not deeper. Nodes are handled in reverse, children before their parents.
+    fn center_parents(plot_data_vec: &mut [TreePlotData]) {
+        for i in (0..plot_data_vec.len()).rev() {
+            let depth = plot_data_vec[i].positional_args[3];
+            let children = (i + 1..plot_data_vec.len())
+            .take_while(|j| plot_data_vec[*j].positional_args[3] > depth)
+            .filter(|j| plot_data_vec[*j].positional_args[3] == depth + 1.0)
+            .collect::<Vec<usize>>();
+            if let (Some(first), Some(last)) = (children.first(), children.last()) {
+                let x = (plot_data_vec[*first].positional_args[2] + plot_data_vec[*last].positional_args[2]) / 2.0;
+                plot_data_vec[i].positional_args[2] = x;
+                for j in children {
+                    plot_data_vec[j].positional_args[0] = x;
+                }
+            }
+        }
+        // the root has no incoming edge, it starts where it ends
+        if let Some(root) = plot_data_vec.first_mut() {
+            root.positional_args[0] = root.positional_args[2];
+        }
+    }
+
+    // A helper method that repositions the nodes by the tidy layout, and returns the number of units it spans.
+    // Every node gets a cell of one unit, the bounds are spread over the units.
+    fn tidy(plot_data_vec: &mut [TreePlotData]) -> usize {
+        let depths = plot_data_vec.iter().map(|plot_data| plot_data.positional_args[3] as usize).collect::<Vec<usize>>();
+        let xs = tidy_positions(&depths);
+        let n_units = xs.iter().cloned().fold(0.0, f32::max).ceil() as usize + 1;
+        let unit = (INIT_RIGHT_BOUND - INIT_LEFT_BOUND) / n_units as f32;
+
+        // in pre order, the parent of a node is the last node seen one level above it
+        let mut ancestors_x: Vec<f32> = Vec::new();
+        for (plot_data, (x, depth)) in plot_data_vec.iter_mut().zip(xs.iter().zip(depths.iter())) {
+            let x = INIT_LEFT_BOUND + (x + 0.5) * unit;
+            ancestors_x.truncate(*depth);
+            let parent_x = ancestors_x.last().cloned().unwrap_or(x);
+            plot_data.positional_args[0] = parent_x;
+            plot_data.positional_args[2] = x;
+            plot_data.positional_args[4] = x - unit / 2.0;
+            plot_data.positional_args[5] = x + unit / 2.0;
+            ancestors_x.push(x);
+        }
+        n_units
+    }
+
+    // A helper method that moves all the leaves down to the deepest level, keeping their horizontal positions.
+    fn align_leaves(plot_data_vec: &mut [TreePlotData]) {
+        let bottom = plot_data_vec.iter().map(|plot_data| plot_data.positional_args[3]).fold(0.0, f32::max);
+        for plot_data in plot_data_vec.iter_mut().filter(|plot_data| plot_data.is_leaf) {
+            plot_data.positional_args[3] = bottom;
+        }
+    }
+
+    // A helper method that returns the lines of a label as drawn, formatted by the user callback if given.
+    fn format_label(&self, label: &str) -> Vec<String> {
+        label.lines().map(|line| match &self.label_format {
+            Some(label_format) => label_format(line),
+            None => line.to_string()
+        }).collect::<Vec<String>>()
+    }
+
+    // A helper method that splits a label at the last subscript separator into the label and its index, see
+    // set_subscript_separator. Returns None if there is no separator, nothing before it, or no numeric index after it.
+    fn split_subscript<'a>(&self, label: &'a str) -> Option<(&'a str, &'a str)> {
+        let separator = self.subscript_separator.as_deref()?;
+        label.rsplit_once(separator)
+        .filter(|(label, index)| !label.is_empty() && !index.is_empty() && index.chars().all(|c| c.is_ascii_digit()))
+    }
+
+    // A helper method that checks whether a node is a preterminal, i.e its only child is a leaf.
+    fn is_preterminal(&self, node_id: &NodeId) -> Result<bool, Box<dyn Error>> {
+        let children = self.tree.get(node_id)?.children();
+        match children.as_slice() {
+            [child_id] => Ok(self.tree.get(child_id)?.children().is_empty()),
+            _ => Ok(false)
+        }
+    }
+
+    // A helper method that returns the label of a node as plotted. When preterminals are merged,
+    // the label of a preterminal is followed by the label of its leaf, in a new line.
+    fn display_label(&self, node_id: &NodeId) -> Result<String, Box<dyn Error>> {
+        let node = self.tree.get(node_id)?;
+        if self.merge_preterminals && self.is_preterminal(node_id)? {
+            let leaf = self.tree.get(&node.children()[0])?;
+            return Ok(format!("{}\n{}", node.data(), leaf.data()));
+        }
+        Ok(node.data().to_owned())
+    }
+
+    // A helper method that draws an animated plot on the given drawing area of a gif backend. Every frame is drawn with
+    // the nodes inserted so far, the layout of the full tree is kept. Warnings are reported once, by the last frame
+    // that holds all the nodes.
+    fn draw_frames<'b, DB: OverlayBackend<'b>>(&mut self, root_area: DrawingArea<DB, Shift>, plot_data_vec: Vec<TreePlotData>, tree_height: usize, font_style: (&str, i32)) -> Result<(), Box<dyn Error>>
+    where DB::ErrorType: 'static {
+
+        let n_warnings = self.warnings.len();
+        for n_nodes in 1..plot_data_vec.len() {
+            self.draw(root_area.clone(), plot_data_vec[..n_nodes].to_vec(), tree_height, font_style)?;
+            self.warnings.truncate(n_warnings);
+        }
+        self.draw(root_area, plot_data_vec, tree_height, font_style)
+    }
+
+    // A helper method that draws the plot on the given drawing area, the backend is determined by the caller.
+    fn draw<'b, DB: OverlayBackend<'b>>(&mut self, root_area: DrawingArea<DB, Shift>, plot_data_vec: Vec<TreePlotData>, tree_height: usize, font_style: (&str, i32)) -> Result<(), Box<dyn Error>>
+    where DB::ErrorType: 'static {
+
+        root_area.fill(&self.background).unwrap();
+        let x_spec = std::ops::Range{start:INIT_LEFT_BOUND, end:INIT_RIGHT_BOUND};
+        // a single node tree has no depth range, it is centered around depth 0 instead
+        let y_spec = match tree_height {
+            1 => std::ops::Range{start: 0.5, end: -0.5},
+            _ => std::ops::Range{start:(tree_height-1) as f32, end: 0.0}
+        };
+
+        // x axis is removed thus doesn't need much space compared to y axis
+        let mut chart = ChartBuilder::on(&root_area)
+        .margin(self.scaled(self.margin))
+        .x_label_area_size(self.scaled(X_LABEL_AREA))
+        .y_label_area_size(self.scaled(Y_LABEL_AREA))
+        .build_cartesian_2d(x_spec, y_spec).unwrap();
+        
+        // the y mesh is kept for the depth gridlines, a faint bold line at every depth label (without light lines between)
+        let mut mesh = chart.configure_mesh();
+        if !self.depth_gridlines {
+            mesh.disable_y_mesh();
+        }
+        mesh
+        .bold_line_style(self.foreground.mix(GRIDLINE_OPACITY).stroke_width(self.scaled(1)))
+        .light_line_style(TRANSPARENT)
+        .axis_style(self.foreground.stroke_width(self.scaled(1)))
+        .disable_x_mesh()
+        .disable_x_axis()
+        .y_labels(tree_height as usize)
+        .y_desc(Y_AX_LABEL)
+        .y_label_style(TextStyle::from(font_style).color(&self.foreground))
+        .axis_desc_style(TextStyle::from(font_style).color(&self.foreground))
+        .y_label_formatter(&|x| format!("{}", *x as i32))
+        .draw()
+        .unwrap();
+
+        // warn about labels that are estimated to overlap their neighbors (unless truncated)
+        if !self.truncate_labels {
+            let area_width = chart.plotting_area().dim_in_pixel().0 as f32;
+            for plot_data in plot_data_vec.iter() {
+                let [left_bound, right_bound]: [f32; 2] = plot_data.positional_args[4..].try_into().unwrap();
+                let cell_width = area_width * (right_bound - left_bound) / (INIT_RIGHT_BOUND - INIT_LEFT_BOUND);
+                if self.format_label(&plot_data.label_arg).iter().any(|line| overflows(line, cell_width, font_style.1)) {
+                    self.warnings.push(Warning::LabelOverflow(plot_data.label_arg.clone()));
+                }
+            }
+        }
+
+        // warn about leaves that are allocated less than the minimal spacing (relative to the font size)
+        if let Some(min_leaf_spacing) = self.min_leaf_spacing {
+            let area_width = chart.plotting_area().dim_in_pixel().0 as f32;
+            let leaf_widths = plot_data_vec.iter()
+            .filter(|plot_data| plot_data.is_leaf)
+            .map(|plot_data| area_width * (plot_data.positional_args[5] - plot_data.positional_args[4]) / (INIT_RIGHT_BOUND - INIT_LEFT_BOUND))
+            .collect::<Vec<f32>>();
+            let n_cramped = leaf_widths.iter().filter(|width| **width < min_leaf_spacing * font_style.1 as f32).count();
+            if n_cramped > 0 {
+                let narrowest = leaf_widths.iter().cloned().fold(f32::INFINITY, f32::min);
+                self.warnings.push(Warning::CrampedLeaves(n_cramped, narrowest));
+            }
+        }
+
+        // the separators are drawn behind the tree, from halfway below the root to the deepest level
+        if self.forest_separators {
+            let separator_style = self.foreground.mix(SEPARATOR_OPACITY).stroke_width(self.scaled(1));
+            let bottom = tree_height.saturating_sub(1) as f32;
+            for x in Tree2Plot::forest_separators(&plot_data_vec) {
+                chart.draw_series(LineSeries::new(vec![(x, 0.5), (x, bottom)], separator_style)).unwrap();
+            }
+        }
+
+        self.plot(&mut chart, plot_data_vec, font_style)?;
+        if let Some(overlay) = &self.overlay {
+            overlay(DB::overlay_chart(&mut chart))?;
+        }
+        root_area.present()?;
+        Ok(())
+    }
+}
+
 impl WalkTree for Tree2Plot {
 
     fn get_root_element(&self) -> Result<Element, Box<dyn Error>> {
@@ -158,6 +989,12 @@ impl WalkTree for Tree2Plot {
     fn get_children_ids(&self, element_id: Element) -> Result<Vec<Element>, Box<dyn Error>> {
 
         let node_id = <&NodeId>::try_from(element_id)?;
+
+        // the leaf of a merged preterminal is plotted as part of its parent
+        if self.merge_preterminals && self.is_preterminal(node_id)? {
+            return Ok(Vec::new());
+        }
+
         let children_ids = self.tree.children_ids(node_id)?.map(|x| Element::NID(x))
         .collect::<Vec<Element>>();
         return Ok(children_ids)
@@ -177,11 +1014,10 @@ impl WalkActions for Tree2Plot {
 
         // get root node label and send with initial positional args to plot
         // bounds are set to -+ 5 but this is arbitrary and not shown on x axis.
-        let root_node = self.tree.get(root_node_id).unwrap();
-        let root_node_data = root_node.data();
         let root_plot_args = TreePlotData {
             positional_args: [0.0, 0.0, 0.0, 0.0, INIT_LEFT_BOUND, INIT_RIGHT_BOUND],
-            label_arg: root_node_data.to_owned()
+            label_arg: self.display_label(root_node_id)?,
+            is_leaf: self.get_children_ids(element_id)?.is_empty()
         };
 
         // A convertion from the general enum Accumulator to the spcecific implementation accumulator(Vec<TreePlotData>) 
@@ -231,7 +1067,7 @@ impl WalkActions for Tree2Plot {
 
         // get label for this child;
         let child_node_id = <&NodeId>::try_from(child_element_id)?;
-        let label = self.tree.get(child_node_id).unwrap().data().to_owned();
+        let label = self.display_label(child_node_id)?;
 
         // calculate positional args for this child
         // for positional computation, get the total number of sub_children that are leaves for this node
@@ -248,7 +1084,8 @@ impl WalkActions for Tree2Plot {
         // create plot data for this child
         let child_walk_args = TreePlotData {
             positional_args: [x2, y2, new_x2, new_y2, new_left_bound, new_right_bound],
-            label_arg: label
+            label_arg: label,
+            is_leaf: self.get_children_ids(child_element_id)?.is_empty()
         };
         
         let data_vec = <&mut Vec<TreePlotData>>::try_from(data)?;
@@ -268,3 +1105,455 @@ impl WalkActions for Tree2Plot {
 
 
 }
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Tree2Plot, TreePlotData, TreeLayout, EdgeStyle};
+    use crate::{String2StructureBuilder, String2Tree, Structure2PlotBuilder, PngCompression, OverlayChart, Warning};
+    use plotters::prelude::{Circle, RED};
+    use crate::generic_enums::Accumulator;
+    use crate::generic_traits::generic_traits::WalkTree;
+    use image::AnimationDecoder;
+    use image::codecs::gif::GifDecoder;
+
+    // returns the width and height of the saved png, as stored in the IHDR chunk
+    fn tree2plot_template(example: &str, save_to: &str, configure: impl Fn(&mut Tree2Plot)) -> (u32, u32) {
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let tree = string2tree.get_structure();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(tree);
+        configure(&mut tree2plot);
+        tree2plot.build(save_to).unwrap();
+
+        let png = std::fs::read(save_to).unwrap();
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        (width, height)
+    }
+
+    #[test]
+    fn cramped_leaves() {
+        let example = "(S (A (B (C (D (E (F (G (H (I (J (K (a)))))))))))) (L (b)) (M (c)))";
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.build("Output/constituency_cramped_leaves.png").unwrap();
+        assert!(!tree2plot.get_warnings().iter().any(|warning| matches!(warning, Warning::CrampedLeaves(..))));
+
+        // the deep branch shrinks the font size much less than the width of the three leaves
+        tree2plot.set_min_leaf_spacing(Some(12.0));
+        tree2plot.build("Output/constituency_cramped_leaves.png").unwrap();
+        let cramped = tree2plot.get_warnings().into_iter().find(|warning| matches!(warning, Warning::CrampedLeaves(..)));
+        assert!(matches!(cramped, Some(Warning::CrampedLeaves(3, _))), "{:?}", cramped);
+
+        tree2plot.set_min_leaf_spacing(Some(0.5));
+        tree2plot.build("Output/constituency_cramped_leaves.png").unwrap();
+        assert!(!tree2plot.get_warnings().iter().any(|warning| matches!(warning, Warning::CrampedLeaves(..))));
+    }
+
+    #[test]
+    fn single_node() {
+        let (width, height) = tree2plot_template("(S)", "Output/constituency_single_node.png", |_| {});
+        assert_eq!((width, height), (640, 640));
+    }
+
+    #[test]
+    fn single_leaf() {
+        let (width, height) = tree2plot_template("(S (a))", "Output/constituency_single_leaf.png", |_| {});
+        assert!(height <= 2 * width, "degenerate single leaf figure of {}x{}", width, height);
+    }
+
+    #[test]
+    fn min_level_spacing() {
+        let example = "(S (A (B (C (D (E (F (G (H x)))))))))";
+        let (_, height) = tree2plot_template(example, "Output/constituency_deep.png", |_| {});
+        assert!(height < 10 * 100);
+
+        let (_, height) = tree2plot_template(example, "Output/constituency_deep.png", |x| x.set_min_level_spacing(Some(100)));
+        assert!(height >= 10 * 100);
+    }
+
+    #[test]
+    fn scale() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let (width, height) = tree2plot_template(example, "Output/constituency_scale.png", |_| {});
+        let scaled = tree2plot_template(example, "Output/constituency_scale.png", |x| x.set_scale(2.0).unwrap());
+        assert_eq!(scaled, (2 * width, 2 * height));
+
+        // the scale is kept when an invalid factor is rejected
+        tree2plot_template(example, "Output/constituency_scale.png", |x| {
+            for invalid in [0.0, -2.0, f32::NAN, f32::INFINITY] {
+                assert!(x.set_scale(invalid).is_err());
+            }
+            assert_eq!(x.scale, 1.0);
+        });
+    }
+
+    #[test]
+    fn horizontal_stretch() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let (width, height) = tree2plot_template(example, "Output/constituency_stretch.png", |_| {});
+        let stretched = tree2plot_template(example, "Output/constituency_stretch.png", |x| x.set_horizontal_stretch(1.5).unwrap());
+        assert_eq!(stretched, ((1.5 * width as f32).round() as u32, height));
+
+        // the stretch is kept when an invalid factor is rejected
+        tree2plot_template(example, "Output/constituency_stretch.png", |x| {
+            for invalid in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+                assert!(x.set_horizontal_stretch(invalid).is_err());
+            }
+            assert_eq!(x.horizontal_stretch, 1.0);
+        });
+    }
+
+    #[test]
+    fn compute_dimensions() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_layout(TreeLayout::Tidy);
+        tree2plot.set_min_level_spacing(Some(200));
+
+        let dimensions = tree2plot_template(example, "Output/constituency_dimensions.png", |x| {
+            x.set_layout(TreeLayout::Tidy);
+            x.set_min_level_spacing(Some(200));
+        });
+        assert_eq!(tree2plot.compute_dimensions().unwrap(), dimensions);
+    }
+
+    #[test]
+    fn sub_root() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_sub_root_by_label("VP").unwrap();
+        assert_eq!(tree2plot.tree.height(), 4);
+        assert_eq!(tree2plot.node_id2n_sub_children[tree2plot.tree.root_node_id().unwrap()], 3);
+        assert!(tree2plot.set_sub_root_by_label("ADJ").is_err());
+    }
+
+    #[test]
+    fn encoding() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        tree2plot_template(example, "Output/constituency_fast.png", |_| {});
+        tree2plot_template(example, "Output/constituency_best.png", |x| x.set_png_compression(PngCompression::Best));
+        let fast = std::fs::metadata("Output/constituency_fast.png").unwrap().len();
+        let best = std::fs::metadata("Output/constituency_best.png").unwrap().len();
+        assert!(best < fast);
+
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_jpeg_quality(50);
+        tree2plot.build("Output/constituency_quality.jpg").unwrap();
+        assert_eq!(std::fs::read("Output/constituency_quality.jpg").unwrap()[..2], [0xFF, 0xD8]);
+    }
+
+    #[test]
+    fn antialiasing() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let colors = |save_to: &str| {
+            let image = image::open(save_to).unwrap().to_rgb8();
+            image.pixels().map(|pixel| pixel.0).collect::<std::collections::HashSet<[u8; 3]>>()
+        };
+
+        // anti-aliased edges and glyphs blend the foreground into the background
+        tree2plot_template(example, "Output/constituency_antialiased.png", |_| {});
+        assert!(colors("Output/constituency_antialiased.png").len() > 2);
+
+        // without anti-aliasing only the background and the foreground are drawn, and the plot is reproducible
+        tree2plot_template(example, "Output/constituency_aliased.png", |x| x.set_antialiasing(false));
+        let aliased = std::fs::read("Output/constituency_aliased.png").unwrap();
+        assert_eq!(colors("Output/constituency_aliased.png"), [[255, 255, 255], [0, 0, 0]].into_iter().collect());
+        tree2plot_template(example, "Output/constituency_aliased.png", |x| x.set_antialiasing(false));
+        assert_eq!(std::fs::read("Output/constituency_aliased.png").unwrap(), aliased);
+    }
+
+    #[test]
+    fn forest_separators() {
+        let example = "(S (NP (N People)) (VP (V watch))) (S (NP (N Time)) (VP (V flies))) (X (y))";
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.set_wrap_forest(true);
+        string2tree.build(&mut example.to_string()).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+
+        // a separator between every two adjacent trees, between their nodes
+        let (plot_data_vec, _) = tree2plot.layout().unwrap();
+        let separators = Tree2Plot::forest_separators(&plot_data_vec);
+        assert_eq!(separators.len(), 2);
+        let leaf_xs = plot_data_vec.iter().filter(|x| x.is_leaf).map(|x| x.positional_args[2]).collect::<Vec<f32>>();
+        assert!(leaf_xs[1] < separators[0] && separators[0] < leaf_xs[2]);
+        assert!(leaf_xs[3] < separators[1] && separators[1] < leaf_xs[4]);
+
+        // the separators are drawn only on request, the dimensions are kept
+        tree2plot.build("Output/constituency_forest.png").unwrap();
+        let plain = std::fs::read("Output/constituency_forest.png").unwrap();
+        tree2plot.set_forest_separators(true);
+        tree2plot.build("Output/constituency_forest.png").unwrap();
+        let separated = std::fs::read("Output/constituency_forest.png").unwrap();
+        assert_ne!(plain, separated);
+        assert_eq!(plain[16..24], separated[16..24]);
+    }
+
+    #[test]
+    fn subscript() {
+        let example = "(S (NP-1 (N John)) (VP (V seems) (S (NP-1 (N e)) (VP (-LRB- to)))))";
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+
+        // split at the last separator only, and only with a label before it and an index after it
+        assert_eq!(tree2plot.split_subscript("NP-1"), None);
+        tree2plot.set_subscript_separator(Some("-"));
+        assert_eq!(tree2plot.split_subscript("NP-SBJ-1"), Some(("NP-SBJ", "1")));
+        for label in ["NP", "-LRB-", "-", "NP-", "NP-SBJ", "well-known"] {
+            assert_eq!(tree2plot.split_subscript(label), None);
+        }
+
+        // the words of the leaves are drawn as is, even with a numeric index
+        let words = "(S (NP-SBJ (ADJ well-known) (N covid-19)))";
+        tree2plot_template(words, "Output/constituency_subscript.png", |_| {});
+        let plain = std::fs::read("Output/constituency_subscript.png").unwrap();
+        tree2plot_template(words, "Output/constituency_subscript.png", |x| x.set_subscript_separator(Some("-")));
+        assert_eq!(std::fs::read("Output/constituency_subscript.png").unwrap(), plain);
+
+        // the subscripts change the drawing, not the dimensions
+        let plain = tree2plot_template(example, "Output/constituency_subscript.png", |_| {});
+        let before = std::fs::read("Output/constituency_subscript.png").unwrap();
+        assert_eq!(tree2plot_template(example, "Output/constituency_subscript.png", |x| x.set_subscript_separator(Some("-"))), plain);
+        assert_ne!(std::fs::read("Output/constituency_subscript.png").unwrap(), before);
+    }
+
+    #[test]
+    fn font() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let font: &'static [u8] = include_bytes!("../fonts/Tuffy.ttf");
+
+        // the texts are drawn in the embedded font instead of the system font, the rest of the plot is the same
+        tree2plot_template(example, "Output/constituency_system_font.png", |x| x.set_antialiasing(false));
+        let dims = tree2plot_template(example, "Output/constituency_embedded_font.png", |x| {
+            x.set_antialiasing(false);
+            x.set_font(Some(font)).unwrap();
+        });
+        let embedded = std::fs::read("Output/constituency_embedded_font.png").unwrap();
+        assert_ne!(std::fs::read("Output/constituency_system_font.png").unwrap(), embedded);
+        assert_eq!(dims, tree2plot_template(example, "Output/constituency_system_font.png", |_| {}));
+
+        tree2plot_template(example, "Output/constituency_embedded_font.png", |x| {
+            x.set_antialiasing(false);
+            x.set_font(Some(font)).unwrap();
+        });
+        assert_eq!(std::fs::read("Output/constituency_embedded_font.png").unwrap(), embedded);
+
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        assert!(tree2plot.set_font(Some(b"not a font")).is_err());
+    }
+
+    #[test]
+    fn gif() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_gif_frame_delay(100);
+        tree2plot.build("Output/constituency_animation.gif").unwrap();
+
+        // a frame for every node
+        let (plot_data_vec, _) = tree2plot.layout().unwrap();
+        let gif = std::fs::File::open("Output/constituency_animation.gif").unwrap();
+        let frames = GifDecoder::new(std::io::BufReader::new(gif)).unwrap().into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), plot_data_vec.len());
+    }
+
+    #[test]
+    fn label_format() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch)))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        assert_eq!(tree2plot.format_label("det\nThe"), vec!["det", "The"]);
+        tree2plot.set_label_format(|label| label.chars().take(2).collect::<String>());
+        assert_eq!(tree2plot.format_label("people"), vec!["pe"]);
+        assert_eq!(tree2plot.format_label("det\nThe"), vec!["de", "Th"]);
+        tree2plot.build("Output/constituency_label_format.png").unwrap();
+    }
+
+    #[test]
+    fn center_parents() {
+        let mut constituency = String::from("(S (A (a) (b) (c)) (B (d)))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        let mut accumulator = Accumulator::TPD(Vec::new());
+        tree2plot.walk(None, &mut accumulator).unwrap();
+        let plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator).unwrap();
+        assert_eq!(plot_data_vec[0].positional_args[2], 0.0);
+
+        // S is moved over the midpoint of A (-1.25) and B (3.75), and its children edges follow
+        Tree2Plot::center_parents(plot_data_vec);
+        let xs = plot_data_vec.iter().map(|x| x.positional_args[2]).collect::<Vec<f32>>();
+        assert_eq!(xs, vec![1.25, -1.25, -3.75, -1.25, 1.25, 3.75, 3.75]);
+        assert_eq!(plot_data_vec[5].positional_args[0], 1.25);
+    }
+
+    #[test]
+    fn equal_leaves() {
+        let mut constituency = String::from("(S (A (a) (b) (c)) (d))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        let mut accumulator = Accumulator::TPD(Vec::new());
+        tree2plot.walk(None, &mut accumulator).unwrap();
+        let plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator).unwrap();
+
+        // the leaf d is moved down to the level of a, b and c
+        Tree2Plot::align_leaves(plot_data_vec);
+        let depths = plot_data_vec.iter().map(|x| x.positional_args[3]).collect::<Vec<f32>>();
+        assert_eq!(depths, vec![0.0, 1.0, 2.0, 2.0, 2.0, 2.0]);
+
+        tree2plot.set_layout(TreeLayout::EqualLeaves);
+        tree2plot.build("Output/constituency_equal_leaves.png").unwrap();
+    }
+
+    #[test]
+    fn tidy() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (ADJ big) (N game))))";
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        let mut accumulator = Accumulator::TPD(Vec::new());
+        tree2plot.walk(None, &mut accumulator).unwrap();
+        let plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator).unwrap();
+
+        // the edges start at the new positions of the parents
+        assert_eq!(Tree2Plot::tidy(plot_data_vec), 6);
+        assert_eq!(plot_data_vec[1].positional_args[0], plot_data_vec[0].positional_args[2]);
+        assert_eq!(plot_data_vec[2].positional_args[0], plot_data_vec[1].positional_args[2]);
+
+        let (width, _) = tree2plot_template(example, "Output/constituency_tidy.png", |x| x.set_layout(TreeLayout::Tidy));
+        let (proportional_width, _) = tree2plot_template(example, "Output/constituency_tidy.png", |_| {});
+        assert!(width <= proportional_width);
+    }
+
+    #[test]
+    fn overlay() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        // a png is drawn by the bitmap backend, whether anti-aliasing is on or off
+        for antialiasing in [true, false] {
+            let counter = calls.clone();
+            tree2plot_template(example, "Output/constituency_overlay.png", |x| {
+                let counter = counter.clone();
+                x.set_antialiasing(antialiasing);
+                x.set_overlay(move |chart| {
+                    counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    match chart {
+                        OverlayChart::Bitmap(chart) => chart.draw_series(std::iter::once(Circle::new((0.0, 1.0), 10, RED)))?,
+                        OverlayChart::Svg(_) => panic!("a png is drawn by the bitmap backend")
+                    };
+                    Ok(())
+                });
+            });
+        }
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn clone() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_label_format(|label| label.to_uppercase());
+        tree2plot.set_layout(TreeLayout::Tidy);
+
+        // the variant keeps the configuration of the original, except for the tweaked option
+        let mut variant = tree2plot.clone();
+        variant.set_layout(TreeLayout::EqualLeaves);
+        assert_eq!(variant.format_label("people"), vec!["PEOPLE"]);
+        assert_eq!((tree2plot.layout, variant.layout), (TreeLayout::Tidy, TreeLayout::EqualLeaves));
+        tree2plot.build("Output/constituency_clone.png").unwrap();
+        variant.build("Output/constituency_clone_variant.png").unwrap();
+
+        // a configured plotter can be built on another thread
+        std::thread::spawn(move || variant.build("Output/constituency_clone_variant.png").unwrap()).join().unwrap();
+    }
+
+    #[test]
+    fn depth_gridlines() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))";
+        let n_lines = |depth_gridlines: bool| {
+            let mut constituency = String::from(example);
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.build(&mut constituency).unwrap();
+            let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2plot.set_depth_gridlines(depth_gridlines);
+            tree2plot.build("Output/constituency_gridlines.svg").unwrap();
+            std::fs::read_to_string("Output/constituency_gridlines.svg").unwrap().matches("<line").count()
+        };
+
+        // a rule at every depth, 0 to 4
+        assert_eq!(n_lines(false), 0);
+        assert_eq!(n_lines(true), 5);
+    }
+
+    #[test]
+    fn edge_style() {
+        let example = "(S (NP (det The) (N people)) (VP (V watch)))";
+        let edge_points = |edge_style: EdgeStyle| {
+            let mut constituency = String::from(example);
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.build(&mut constituency).unwrap();
+            let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2plot.set_edge_style(edge_style);
+            tree2plot.build("Output/constituency_edges.svg").unwrap();
+            let svg = std::fs::read_to_string("Output/constituency_edges.svg").unwrap();
+            svg.split("<polyline").skip(1).map(|x| x.split("points=\"").nth(1).unwrap().split('"').next().unwrap().split_whitespace().count()).collect::<Vec<usize>>()
+        };
+
+        // an edge per non root node, of three segments when orthogonal (the axis is drawn with single segments)
+        let straight = edge_points(EdgeStyle::Straight);
+        let orthogonal = edge_points(EdgeStyle::Orthogonal);
+        assert_eq!(straight.len(), orthogonal.len());
+        assert_eq!(straight.iter().filter(|x| **x == 4).count(), 0);
+        assert_eq!(orthogonal.iter().filter(|x| **x == 4).count(), 8);
+    }
+
+    #[test]
+    fn merge_preterminals() {
+        let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+
+        let mut tree2plot: Tree2Plot = Structure2PlotBuilder::new(string2tree.get_structure());
+        tree2plot.set_merge_preterminals(true);
+        let mut accumulator = Accumulator::TPD(Vec::new());
+        tree2plot.walk(None, &mut accumulator).unwrap();
+
+        let plot_data_vec = <&mut Vec<TreePlotData>>::try_from(&mut accumulator).unwrap();
+        let labels = plot_data_vec.iter().map(|x| x.label_arg.as_str()).collect::<Vec<&str>>();
+        assert_eq!(labels, vec!["S", "NP", "det\nThe", "N\npeople", "VP", "V\nwatch", "NP", "det\nthe", "N\ngame"]);
+        assert_eq!(plot_data_vec.iter().map(|x| x.positional_args[3]).fold(0.0, f32::max), 3.0);
+        tree2plot.build("Output/constituency_merged.png").unwrap();
+    }
+}
@@ -5,25 +5,156 @@
 
 use id_tree::*;
 use std::error::Error;
+use std::fs::File;
+use std::io::Write;
 
 use super::config::configure_structures::Saver;
+use super::string_2_tree::{escape_label, split_once_unescaped, split_unescaped, FOREST_ROOT};
 use super::generic_enums::{Accumulator, Element};
 use super::generic_traits::generic_traits::{WalkActions, WalkTree, Structure2PlotBuilder};
 
 const CLOSE_BRACKET: &str = ")";
 const OPEN_BRACKET: &str = "(";
+const NODE_DELIMITER: char = ' ';
+const INFIX_DELIMITER: &str = " ";
 
 /// A Tree2String struct, mainly holds the tree object. This type will implement Structure2PlotBuilder,
 /// WalkTree and WalkActions, with an ultimate goal of saving a constituency string of the tree to file.
  pub struct Tree2String {
     tree: Tree<String>,
-    output: Option<String>
+    output: Option<String>,
+    infix: bool,
+    escapes: bool,
+    forest_separator: Option<String>
 }
 
 impl Tree2String {
 
+    ///
+    /// A set method to determine whether the tree is reconstructed in infix notation, "(1 + 2) * 3", rather than in
+    /// the bracketed prefix form (default false). Meant for expression trees with singular leaves, inner nodes
+    /// are taken as operators between their children and leaves as operands. A unary operator is written before
+    /// its operand, and every compound operand is wrapped in brackets (precedence is not assumed).
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2String;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut expression = String::from("(* (+ (1) (2)) (3))");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.build(&mut expression).unwrap();
+    /// 
+    /// let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2string.set_infix(true);
+    /// tree2string.build("Output/expression_infix.txt").unwrap();
+    /// assert_eq!(tree2string.get_constituency(false), "(1 + 2) * 3");
+    /// ```
+    ///
+    pub fn set_infix(&mut self, infix: bool) {
+        self.infix = infix;
+    }
+
+    ///
+    /// A set method to determine whether spaces, brackets and backslashes in the labels are escaped with a backslash
+    /// (default false), as String2Tree::set_escapes parses them. Without it, such labels give a string that
+    /// can't be parsed back. Should be called before build.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2String;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let example = "(NP (N New\\ York) (PUNCT \\)))";
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.set_escapes(true);
+    /// string2tree.build(&mut example.to_string()).unwrap();
+    /// 
+    /// let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2string.set_escapes(true);
+    /// tree2string.build("Output/constituency_escapes.txt").unwrap();
+    /// assert_eq!(tree2string.get_constituency(true), example);
+    /// ```
+    ///
+    pub fn set_escapes(&mut self, escapes: bool) {
+        self.escapes = escapes;
+    }
+
+    ///
+    /// A set method to determine whether the tree is a forest under a synthetic root (see String2Tree::set_wrap_forest),
+    /// reconstructed as its top level trees joined by the given separator, like " " as in the parsed string or "\n"
+    /// for one tree per line (default None, the tree is reconstructed as a single tree). The synthetic root is not written.
+    /// A tree that was not wrapped is reconstructed as is, so the separator can be set for a whole corpus: only a root
+    /// labeled as the synthetic root with several children (as String2Tree wraps a forest) is left out.
+    /// 
+    /// # Examples
+    /// 
+    /// ```
+    /// use parsed_to_plot::String2Tree;
+    /// use parsed_to_plot::Tree2String;
+    /// use parsed_to_plot::String2StructureBuilder;
+    /// use parsed_to_plot::Structure2PlotBuilder;
+    /// 
+    /// let mut constituency = String::from("(+ (1) (2)) (3)");
+    /// let mut string2tree: String2Tree = String2StructureBuilder::new();
+    /// string2tree.set_wrap_forest(true);
+    /// string2tree.build(&mut constituency).unwrap();
+    /// 
+    /// let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+    /// tree2string.set_forest_separator(Some("\n"));
+    /// tree2string.build("Output/constituency_forest.txt").unwrap();
+    /// assert_eq!(tree2string.get_constituency(false), "(+ (1) (2))\n(3)");
+    /// ```
+    ///
+    pub fn set_forest_separator(&mut self, forest_separator: Option<&str>) {
+        self.forest_separator = forest_separator.map(|x| x.to_string());
+    }
+
+    ///
+    /// A method to build the constituency string from the tree and write it to any writer (an in-memory
+    /// buffer, an open file, etc.) rather than to a file path. The string can be retrieved afterwards as in build().
+    ///
+    pub fn build_to_writer<W: Write>(&mut self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+
+        // the top level trees are the children of the synthetic root of a forest, or the root itself
+        let root_node_id = self.tree.root_node_id().ok_or("tree is empty")?;
+        let root = self.tree.get(root_node_id)?;
+        let top_level_ids = match self.forest_separator.is_some() && root.data() == FOREST_ROOT && root.children().len() > 1 {
+            true => root.children().clone(),
+            false => vec![root_node_id.clone()]
+        };
+
+        // run the recursive extraction on every top level tree
+        let mut trees: Vec<String> = Vec::new();
+        for node_id in top_level_ids.iter() {
+            let mut accumulator = Accumulator::T2S(String::from(""));
+            match self.infix {
+                true => accumulator = Accumulator::T2S(self.infix_string(node_id)?),
+                false => self.walk(Some(Element::NID(node_id)), &mut accumulator)?
+            }
+
+            // move from accumulator to string
+            trees.push(<&mut String>::try_from(&mut accumulator)?.clone());
+        }
+        let prediction = trees.join(self.forest_separator.as_deref().unwrap_or(""));
+
+        // write and set output
+        vec![prediction.clone()].write_output(writer)?;
+        self.output = Some(prediction);
+
+        Ok(())
+    }
+
+    ///
     /// A method to retrieve the constituency string after building it from the tree.
     /// Can be called only after build() has been called. See example on lib.rs.
+    ///
     pub fn get_constituency(self, inverse: bool) -> String {
 
         assert!(self.output.is_some(), "build() most be evoked before retrival of constituency");
@@ -33,13 +164,13 @@ impl Tree2String {
         // for the purpse of checking the inverse tree2string(string2tree(x)) = x, one can use the inverse
         // flag to return the original. This option can have unexpected results for non-double leaf trees.
 
+        // escaped spaces and brackets (see set_escapes) belong to the labels
         if inverse {
-            constituency.split(' ').map(|x| {
-                if x.starts_with(OPEN_BRACKET) && x.ends_with(CLOSE_BRACKET) {
-                    let (left, right) = x.split_once(CLOSE_BRACKET).unwrap();
-                    left.split_once(OPEN_BRACKET).unwrap().1.to_string() + right
-                } else {
-                    x.to_string()
+            let close_bracket = CLOSE_BRACKET.chars().next().unwrap();
+            split_unescaped(&constituency, NODE_DELIMITER, self.escapes).into_iter().map(|x| {
+                match split_once_unescaped(x, close_bracket, self.escapes) {
+                    Some((left, right)) if x.starts_with(OPEN_BRACKET) => left.split_once(OPEN_BRACKET).unwrap().1.to_string() + right,
+                    _ => x.to_string()
                 }
             }).collect::<Vec<String>>().join(" ").to_string()
         } else {
@@ -47,6 +178,35 @@ impl Tree2String {
         }
     }
 
+    // A method that returns the label of a node as written to the string, escaped when set_escapes is on.
+    fn label(&self, node_id: &NodeId) -> Result<String, Box<dyn Error>> {
+        let node_data = self.tree.get(node_id)?.data();
+        match self.escapes {
+            true => Ok(escape_label(node_data)),
+            false => Ok(node_data.to_owned())
+        }
+    }
+
+    // A recursive method that writes the subtree of a node in infix notation, see set_infix.
+    fn infix_string(&self, node_id: &NodeId) -> Result<String, Box<dyn Error>> {
+
+        let node = self.tree.get(node_id)?;
+        let mut operands = Vec::new();
+        for child_id in node.children() {
+            let operand = self.infix_string(child_id)?;
+            match self.tree.get(child_id)?.children().is_empty() {
+                true => operands.push(operand),
+                false => operands.push(format!("{}{}{}", OPEN_BRACKET, operand, CLOSE_BRACKET))
+            }
+        }
+
+        match operands.len() {
+            0 => Ok(node.data().to_owned()),
+            1 => Ok(format!("{}{}", node.data(), operands[0])),
+            _ => Ok(operands.join(&format!("{}{}{}", INFIX_DELIMITER, node.data(), INFIX_DELIMITER)))
+        }
+    }
+
 }
 
 
@@ -56,25 +216,16 @@ impl Structure2PlotBuilder<Tree<String>> for Tree2String {
 
         Self {
             tree: structure,
-            output: None
+            output: None,
+            infix: false,
+            escapes: false,
+            forest_separator: None
         }
     }
 
     fn build(&mut self, save_to: &str) -> Result<(), Box<dyn Error>> {
-        
-        // run the recursive extraction
-        let mut accumulator = Accumulator::T2S(String::from(""));
-        self.walk(None, &mut accumulator)?;
-
-        // move from accumulator to string
-        let prediction = <&mut String>::try_from(&mut accumulator).unwrap();
-
-        // save to file and set output
-        vec![prediction.clone()].save_output(save_to)?;
-        self.output = Some(prediction.clone());
-
-        Ok(())
-
+        let mut out_file = File::create(save_to)?;
+        self.build_to_writer(&mut out_file)
     }
 
 }
@@ -111,7 +262,7 @@ impl WalkActions for Tree2String {
         // double leaves are ignored in the tree2string construction, every leaf is build as if it
         // was a singular leaf (with parenthesis)
         let data_str = <&mut String>::try_from(data)?; 
-        let node_data = self.tree.get(node_id)?.data();
+        let node_data = self.label(node_id)?;
         let sep = if data_str.is_empty() { "" } else { " " };
         *data_str += &format!("{}{}{}{}", sep, OPEN_BRACKET.to_string(), node_data, CLOSE_BRACKET.to_string());
         Ok(())
@@ -120,7 +271,7 @@ impl WalkActions for Tree2String {
     fn on_node(&self, element_id: Element, _parameters: &mut [f32; 6], data: &mut Accumulator) -> Result<(), Box<dyn Error>> {
 
         let node_id = <&NodeId>::try_from(element_id)?;
-        let node_data = self.tree.get(node_id)?.data();
+        let node_data = self.label(node_id)?;
         let data_str = <&mut String>::try_from(data)?;
         let sep = if data_str.is_empty() { "" } else { " " };
         *data_str += &format!("{}{}{}", sep, OPEN_BRACKET.to_string(), node_data);
@@ -172,6 +323,127 @@ mod tests {
         assert_eq!(example, prediction, "\nfailed, original example:\n {}\n != \nprediction: {}", example, prediction);
     } 
 
+    #[test]
+    fn tree_to_writer() {
+
+        let example = String::from("(36 (9 (3) (3)) (4 (2) (2)))");
+        let mut constituency = example.clone();
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        let tree = string2tree.get_structure();
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut tree2string: Tree2String = Structure2PlotBuilder::new(tree);
+        tree2string.build_to_writer(&mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), example);
+    }
+
+    #[test]
+    fn infix() {
+
+        for (example, golden) in [
+            ("(* (+ (1) (2)) (3))", "(1 + 2) * 3"),
+            ("(+ (1) (2) (- (3)))", "1 + 2 + (-3)"),
+            ("(- (+ (x) (1)))", "-(x + 1)"),
+            ("(7)", "7")
+        ] {
+            let mut expression = String::from(example);
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.build(&mut expression).unwrap();
+
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2string.set_infix(true);
+            tree2string.build_to_writer(&mut buffer).unwrap();
+            assert_eq!(String::from_utf8(buffer).unwrap(), golden);
+        }
+    }
+
+    // A minimal linear congruential generator, so the random examples are reproducible without extra dependencies.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound
+        }
+    }
+
+    // generates a random balanced constituency in single leaf mode, i.e every node is wrapped in brackets
+    fn random_constituency(rng: &mut Lcg, depth: usize) -> String {
+        let labels = ["S", "NP", "VP", "det", "N", "people", "3", "a.b", "x-y"];
+        let label = labels[rng.next(labels.len())];
+        let n_children = if depth == 0 { 0 } else { rng.next(4) };
+        let children = (0..n_children).map(|_| String::from(" ") + &random_constituency(rng, depth - 1)).collect::<String>();
+        format!("({}{})", label, children)
+    }
+
+    #[test]
+    fn random_round_trip() {
+
+        let mut rng = Lcg(2023);
+        for _ in 0..200 {
+            let depth = 1 + rng.next(5);
+            let example = random_constituency(&mut rng, depth);
+            let mut constituency = example.clone();
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.build(&mut constituency).unwrap();
+
+            let mut buffer: Vec<u8> = Vec::new();
+            let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2string.build_to_writer(&mut buffer).unwrap();
+            assert_eq!(String::from_utf8(buffer).unwrap(), example);
+        }
+    }
+
+    #[test]
+    fn escapes() {
+
+        for (example, inverse) in [
+            ("(NP (N New\\ York) (PUNCT \\)) (SYM \\\\))", true),
+            ("(S (a\\ \\(b\\)) (c))", false)
+        ] {
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.set_escapes(true);
+            string2tree.build(&mut example.to_string()).unwrap();
+
+            let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2string.set_escapes(true);
+            tree2string.build("Output/constituency_inverse_escapes.txt").unwrap();
+            assert_eq!(tree2string.get_constituency(inverse), example);
+        }
+    }
+
+    #[test]
+    fn forest() {
+
+        for (example, separator, inverse) in [
+            ("(NP (det The) (N people)) (VP (V watch))", " ", true),
+            ("(+ (1) (2))\n(3)", "\n", false)
+        ] {
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.set_wrap_forest(true);
+            string2tree.build(&mut example.replace('\n', " ")).unwrap();
+
+            let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2string.set_forest_separator(Some(separator));
+            tree2string.build("Output/constituency_inverse_forest.txt").unwrap();
+            assert_eq!(tree2string.get_constituency(inverse), example);
+        }
+
+        // the lines of a mixed corpus that were not wrapped keep their roots
+        for example in ["(S (NP (N Time)) (VP (V flies)))", "(ROOT (S (N Time)))"] {
+            let mut string2tree: String2Tree = String2StructureBuilder::new();
+            string2tree.set_wrap_forest(true);
+            string2tree.build(&mut example.to_string()).unwrap();
+
+            let mut tree2string: Tree2String = Structure2PlotBuilder::new(string2tree.get_structure());
+            tree2string.set_forest_separator(Some("\n"));
+            tree2string.build("Output/constituency_inverse_forest.txt").unwrap();
+            assert_eq!(tree2string.get_constituency(true), example);
+        }
+    }
+
     fn inverse_check(example: String, save_to: String, inverse: bool) -> String { 
 
         // check by building tree and returning to the original input, expecting x = f(f^-1(x))
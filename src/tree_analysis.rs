@@ -0,0 +1,307 @@
+//
+// Under MIT license
+//
+
+use id_tree::{Tree, NodeId};
+use std::collections::HashMap;
+use std::error::Error;
+use super::sub_tree_children::sub_tree_children::SubChildren;
+
+// A helper DFS that keeps the stack of labels from the root to the current node,
+// and records a copy of the stack whenever a leaf is reached.
+fn collect_paths(tree: &Tree<String>, node_id: &NodeId, stack: &mut Vec<String>, paths: &mut Vec<Vec<String>>) {
+
+    let node = tree.get(node_id).unwrap();
+    stack.push(node.data().clone());
+
+    if node.children().is_empty() {
+        paths.push(stack.clone());
+    } else {
+        for child_id in node.children() {
+            collect_paths(tree, child_id, stack, paths);
+        }
+    }
+
+    stack.pop();
+}
+
+///
+/// A method that returns every path from the root to a leaf of a constituency tree, as vectors of node labels.
+/// The paths are ordered by the order of the leaves (left to right). Returns an empty vector for an empty tree.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::root_to_leaf_paths;
+///
+/// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch)))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+/// let tree = string2tree.get_structure();
+///
+/// let paths = root_to_leaf_paths(&tree);
+/// assert_eq!(paths[0], vec!["S", "NP", "det", "The"]);
+/// assert_eq!(paths.len(), 3);
+/// ```
+///
+pub fn root_to_leaf_paths(tree: &Tree<String>) -> Vec<Vec<String>> {
+
+    let mut paths: Vec<Vec<String>> = Vec::new();
+    if let Some(root_id) = tree.root_node_id() {
+        collect_paths(tree, root_id, &mut Vec::new(), &mut paths);
+    }
+    paths
+}
+
+// A helper that returns the number of children of every internal (non leaf) node of the tree, in pre order.
+fn internal_children_counts(tree: &Tree<String>) -> Vec<usize> {
+
+    let root_id = match tree.root_node_id() {
+        Some(root_id) => root_id,
+        None => return Vec::new()
+    };
+
+    tree.traverse_pre_order_ids(root_id).unwrap()
+    .filter(|node_id| !tree.is_leaf(node_id).unwrap())
+    .map(|node_id| tree.children_ids(&node_id).unwrap().count())
+    .collect::<Vec<usize>>()
+}
+
+///
+/// A method that returns the average number of children per internal (non leaf) node of a constituency tree.
+/// A chain has a branching factor of 1. Returns 0 for a tree without internal nodes (a single node or empty).
+///
+pub fn branching_factor(tree: &Tree<String>) -> f32 {
+    let counts = internal_children_counts(tree);
+    match counts.len() {
+        0 => 0.0,
+        n => counts.iter().sum::<usize>() as f32 / n as f32
+    }
+}
+
+///
+/// A method that checks whether a constituency tree is a degenerate chain, i.e every node has at most one child.
+/// Such trees are drawn as a thin vertical line.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::is_chain;
+///
+/// let mut constituency = String::from("(S (VP (V watch)))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+///
+/// assert!(is_chain(&string2tree.get_structure()));
+/// ```
+///
+pub fn is_chain(tree: &Tree<String>) -> bool {
+    internal_children_counts(tree).iter().all(|count| *count <= 1)
+}
+
+// A helper that descends from a node to its leftmost (first children) or rightmost (last children) leaf.
+fn extreme_leaf(tree: &Tree<String>, node_id: &NodeId, rightmost: bool) -> Result<NodeId, Box<dyn Error>> {
+
+    let mut current = node_id.clone();
+    loop {
+        let children = tree.get(&current)?.children();
+        let next = if rightmost { children.last() } else { children.first() };
+        match next {
+            Some(child_id) => current = child_id.clone(),
+            None => return Ok(current)
+        }
+    }
+}
+
+///
+/// A method that returns the span of a node in a constituency tree, the indices of its first and last leaves
+/// (inclusive) among the leaves of the tree from left to right. A leaf spans only itself. The span is found
+/// by descending to the leftmost and rightmost leaves of the node, without computing the spans of other nodes.
+/// Returns an error if the node is not in the tree.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::node_span;
+///
+/// let mut constituency = String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut constituency).unwrap();
+/// let tree = string2tree.get_structure();
+///
+/// let root_id = tree.root_node_id().unwrap();
+/// let vp_id = tree.get(root_id).unwrap().children()[1].clone();
+/// assert_eq!(node_span(&tree, &vp_id).unwrap(), (2, 4));
+/// ```
+///
+pub fn node_span(tree: &Tree<String>, node_id: &NodeId) -> Result<(usize, usize), Box<dyn Error>> {
+
+    let (first, last) = (extreme_leaf(tree, node_id, false)?, extreme_leaf(tree, node_id, true)?);
+    let root_id = tree.root_node_id().ok_or("the tree is empty")?;
+    let leaves = tree.traverse_pre_order_ids(root_id)?.filter(|leaf_id| tree.get(leaf_id).is_ok_and(|leaf| leaf.children().is_empty()));
+
+    let mut start = 0;
+    for (i, leaf_id) in leaves.enumerate() {
+        if leaf_id == first {
+            start = i;
+        }
+        if leaf_id == last {
+            return Ok((start, i));
+        }
+    }
+    Err("the node is not in the tree".into())
+}
+
+// A helper that counts the labeled spans (label, first leaf, last leaf) of the constituents of a tree, the inner nodes
+// other than preterminals. A multiset, since a unary chain of the same label has the same labeled span more than once.
+fn labeled_spans(tree: &Tree<String>) -> HashMap<(String, usize, usize), usize> {
+
+    let mut spans: HashMap<(String, usize, usize), usize> = HashMap::new();
+    let root_id = match tree.root_node_id() {
+        Some(root_id) => root_id,
+        None => return spans
+    };
+
+    for node_id in tree.traverse_pre_order_ids(root_id).unwrap() {
+        let children = tree.get(&node_id).unwrap().children();
+        let is_preterminal = children.len() == 1 && tree.is_leaf(&children[0]).unwrap();
+        if children.is_empty() || is_preterminal {
+            continue;
+        }
+        let (start, end) = node_span(tree, &node_id).unwrap();
+        *spans.entry((tree.get(&node_id).unwrap().data().clone(), start, end)).or_insert(0) += 1;
+    }
+    spans
+}
+
+///
+/// A method that compares a predicted constituency tree to a gold tree of the same sentence by their labeled spans,
+/// as in evalb, and returns the (precision, recall, f1). The constituents are the inner nodes other than preterminals
+/// (nodes whose only child is a leaf), each with its label and span, see node_span. Repeated labeled spans (unary chains)
+/// are matched as a multiset, so a span repeated in one tree matches at most as many times as it appears in the other.
+/// A ratio over no constituents is 0. The trees are assumed to have the same leaves.
+///
+/// # Examples
+///
+/// ```
+/// use parsed_to_plot::String2Tree;
+/// use parsed_to_plot::String2StructureBuilder;
+/// use parsed_to_plot::span_f1;
+///
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut String::from("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))")).unwrap();
+/// let gold = string2tree.get_structure();
+/// let mut string2tree: String2Tree = String2StructureBuilder::new();
+/// string2tree.build(&mut String::from("(S (NP (det The) (N people) (V watch)) (VP (NP (det the) (N game))))")).unwrap();
+///
+/// // S and the inner NP match, out of 4 constituents in each tree
+/// assert_eq!(span_f1(&gold, &string2tree.get_structure()), (0.5, 0.5, 0.5));
+/// ```
+///
+pub fn span_f1(gold: &Tree<String>, pred: &Tree<String>) -> (f64, f64, f64) {
+
+    let (gold_spans, pred_spans) = (labeled_spans(gold), labeled_spans(pred));
+    let matched = pred_spans.iter()
+    .map(|(span, count)| *count.min(gold_spans.get(span).unwrap_or(&0)))
+    .sum::<usize>();
+
+    let ratio = |total: usize| match total {
+        0 => 0.0,
+        _ => matched as f64 / total as f64
+    };
+    let (precision, recall) = (ratio(pred_spans.values().sum()), ratio(gold_spans.values().sum()));
+    let f1 = match matched {
+        0 => 0.0,
+        _ => 2.0 * precision * recall / (precision + recall)
+    };
+    (precision, recall, f1)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use id_tree::Tree;
+    use super::{root_to_leaf_paths, branching_factor, is_chain, node_span, span_f1};
+    use crate::{String2StructureBuilder, String2Tree};
+
+    fn tree_template(example: &str) -> Tree<String> {
+        let mut constituency = String::from(example);
+        let mut string2tree: String2Tree = String2StructureBuilder::new();
+        string2tree.build(&mut constituency).unwrap();
+        string2tree.get_structure()
+    }
+
+    #[test]
+    fn paths() {
+        let tree = tree_template("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+
+        let golden = vec![
+            vec!["S", "NP", "det", "The"],
+            vec!["S", "NP", "N", "people"],
+            vec!["S", "VP", "V", "watch"],
+            vec!["S", "VP", "NP", "det", "the"],
+            vec!["S", "VP", "NP", "N", "game"]
+        ];
+        assert_eq!(root_to_leaf_paths(&tree), golden);
+    }
+
+    #[test]
+    fn branching() {
+        let tree = tree_template("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        assert_eq!(branching_factor(&tree), 13.0 / 9.0);
+        assert!(!is_chain(&tree));
+
+        let chain = tree_template("(S (A (B (C x))))");
+        assert_eq!(branching_factor(&chain), 1.0);
+        assert!(is_chain(&chain));
+    }
+
+    #[test]
+    fn spans() {
+        let tree = tree_template("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        let root_id = tree.root_node_id().unwrap();
+        let ids = tree.traverse_pre_order_ids(root_id).unwrap().collect::<Vec<_>>();
+
+        // root, internal nodes (NP, V, the inner NP) and leaves (The, game)
+        assert_eq!(node_span(&tree, root_id).unwrap(), (0, 4));
+        assert_eq!(node_span(&tree, &ids[1]).unwrap(), (0, 1));
+        assert_eq!(node_span(&tree, &ids[7]).unwrap(), (2, 2));
+        assert_eq!(node_span(&tree, &ids[9]).unwrap(), (3, 4));
+        assert_eq!(node_span(&tree, &ids[3]).unwrap(), (0, 0));
+        assert_eq!(node_span(&tree, &ids[13]).unwrap(), (4, 4));
+
+        // a node of another tree
+        let other = tree_template("(S (A a) (B b))");
+        let other_ids = other.traverse_pre_order_ids(other.root_node_id().unwrap()).unwrap().collect::<Vec<_>>();
+        assert!(node_span(&tree, &other_ids[1]).is_err());
+    }
+
+    #[test]
+    fn f1() {
+        let gold = tree_template("(S (NP (det The) (N people)) (VP (V watch) (NP (det the) (N game))))");
+        assert_eq!(span_f1(&gold, &gold), (1.0, 1.0, 1.0));
+
+        // a relabeled constituent does not match, the preterminals are not counted
+        let relabeled = tree_template("(S (NP (det The) (N people)) (XP (V watch) (NP (D the) (N game))))");
+        assert_eq!(span_f1(&gold, &relabeled), (0.75, 0.75, 0.75));
+
+        // the unary NP repeats the span of the inner NP, which is matched only once
+        let unary = tree_template("(S (NP (det The) (N people)) (VP (V watch) (NP (NP (det the) (N game)))))");
+        let (precision, recall, f1) = span_f1(&gold, &unary);
+        assert_eq!((precision, recall), (0.8, 1.0));
+        assert!((f1 - 8.0 / 9.0).abs() < 1e-9);
+        assert_eq!(span_f1(&unary, &gold), (1.0, 0.8, f1));
+
+        // no constituents at all
+        let flat = tree_template("(S (x))");
+        assert_eq!(span_f1(&gold, &flat), (0.0, 0.0, 0.0));
+    }
+
+}
@@ -0,0 +1,31 @@
+//
+// Under MIT license
+//
+
+use std::fmt;
+
+/// An enum of the adjustments that builders make silently during build, without stopping the process.
+/// Warnings are accumulated during build and can be retrieved after the call, using get_warnings().
+#[derive(Clone, Debug, PartialEq)]
+pub enum Warning {
+    /// The computed figure dimensions were too small and were clamped (computed, used).
+    DimensionsClamped((u32, u32), (u32, u32)),
+    /// An input line was skipped (line number, reason).
+    LineSkipped(usize, String),
+    /// A label is estimated to be wider than the space allocated to it, and may overlap its neighbors.
+    LabelOverflow(String),
+    /// Leaves of a tree are allocated less than the requested minimal spacing (number of leaves, narrowest width in pixels).
+    CrampedLeaves(usize, f32)
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::DimensionsClamped(computed, used) => write!(f, "figure dimensions {:?} were clamped to {:?}", computed, used),
+            Warning::LineSkipped(line, reason) => write!(f, "line {} was skipped: {}", line, reason),
+            Warning::LabelOverflow(label) => write!(f, "label {} is wider than its allocated space", label),
+            Warning::CrampedLeaves(n_leaves, width) => write!(f, "{} leaves are allocated less than the minimal spacing (narrowest is {:.1} pixels), \
+            consider a different layout or a horizontal stretch", n_leaves, width)
+        }
+    }
+}